@@ -1,46 +1,70 @@
 use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::textencoding::TextEncoding;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     #[serde(default = "default_theme")]
     pub theme: String,
+    /// Memory budget in MiB for keeping loaded layers in memory
+    #[serde(default = "default_layer_cache_size")]
+    pub layer_cache_size: usize,
+    /// The maximum number of rows that is read from a database table, 0 reads all the rows
+    #[serde(default = "default_database_row_limit")]
+    pub database_row_limit: usize,
+    /// The character encoding of csv files, it is detected when not set
+    #[serde(default)]
+    pub encoding: Option<TextEncoding>,
 }
 
 fn default_theme() -> String {
     "Default Light".to_string()
 }
 
+fn default_layer_cache_size() -> usize {
+    1024
+}
+
+fn default_database_row_limit() -> usize {
+    100_000
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         AppConfig {
             theme: default_theme(),
+            layer_cache_size: default_layer_cache_size(),
+            database_row_limit: default_database_row_limit(),
+            encoding: None,
         }
     }
 }
 
-/// Get the default config file path using XDG config directory
+/// Get the default config directory using XDG config directory
 ///
 /// Respects $XDG_CONFIG_HOME if set, otherwise falls back to platform defaults:
 /// - Linux/BSD: ~/.config
 /// - macOS: ~/Library/Application Support
 /// - Windows: %APPDATA%
-fn get_default_config_path() -> Option<PathBuf> {
+pub fn get_default_config_dir() -> Option<PathBuf> {
     // Check for XDG_CONFIG_HOME first (cross-platform)
     if let Ok(xdg_config) = std::env::var("XDG_CONFIG_HOME") {
         if !xdg_config.is_empty() {
-            return Some(
-                PathBuf::from(xdg_config)
-                    .join("tabulite")
-                    .join("config.toml"),
-            );
+            return Some(PathBuf::from(xdg_config).join("tabulite"));
         }
     }
 
     // Fall back to platform-specific config directory
-    dirs::config_dir().map(|config_dir| config_dir.join("tabulite").join("config.toml"))
+    dirs::config_dir().map(|config_dir| config_dir.join("tabulite"))
+}
+
+/// Get the default config file path, located in the default config directory
+fn get_default_config_path() -> Option<PathBuf> {
+    get_default_config_dir().map(|config_dir| config_dir.join("config.toml"))
 }
 
 pub fn load_config(config_path: Option<&Path>) -> AppConfig {
@@ -89,6 +113,50 @@ fn load_config_from_path(path: &Path) -> Result<AppConfig> {
     Ok(config)
 }
 
+/// Read and parse a TOML file, the description names the contents in the error messages
+pub fn load_toml<T: DeserializeOwned>(path: &Path, description: &str) -> Result<T> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {description}: {}", path.display()))?;
+
+    toml::from_str(&contents)
+        .with_context(|| format!("Failed to parse {description}: {}", path.display()))
+}
+
+/// Write the value as TOML, the parent directory is created when it does not exist
+pub fn save_toml<T: Serialize>(value: &T, path: &Path, description: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+    }
+
+    fs::write(path, toml::to_string(value)?)
+        .with_context(|| format!("Failed to write {description}: {}", path.display()))
+}
+
+/// Load the application data with the given file name that is stored next to the default config file
+/// Returns the default value when the file does not exist or fails to load, together with the
+/// path to store the data, which is `None` when the config directory cannot be determined
+pub fn load_stored<T: DeserializeOwned + Default>(
+    name: &str,
+    description: &str,
+) -> (T, Option<PathBuf>) {
+    let Some(path) = get_default_config_dir().map(|dir| dir.join(name)) else {
+        log::warn!("Could not determine config directory, {description} will not be stored");
+        return (T::default(), None);
+    };
+
+    let value = if path.exists() {
+        load_toml(&path, description).unwrap_or_else(|err| {
+            log::warn!("Failed to load {description}: {err:#}");
+            T::default()
+        })
+    } else {
+        T::default()
+    };
+
+    (value, Some(path))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -107,6 +175,9 @@ theme = "light"
 
         let config = load_config(Some(&config_path));
         assert_eq!(config.theme, "light");
+        assert_eq!(config.layer_cache_size, 1024);
+        assert_eq!(config.database_row_limit, 100_000);
+        assert_eq!(config.encoding, None);
 
         fs::remove_file(config_path).ok();
     }
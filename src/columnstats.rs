@@ -0,0 +1,196 @@
+use polars::prelude::{
+    ChunkAgg, ChunkQuantile, ChunkVar, DataType, PolarsResult, QuantileMethod, Series,
+};
+
+use crate::valuefilter::{self, ValueCount};
+
+/// The number of most frequent values that are listed for a column
+const TOP_VALUES: usize = 10;
+const HISTOGRAM_BINS: usize = 10;
+const QUANTILES: &[f64] = &[0.05, 0.25, 0.75, 0.95];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: Option<f64>,
+    /// The quantile and its value
+    pub quantiles: Vec<(f64, f64)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistogramBin {
+    pub label: String,
+    pub count: usize,
+}
+
+/// Summary statistics of a column
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    pub name: String,
+    pub dtype: DataType,
+    pub count: usize,
+    pub null_count: usize,
+    pub distinct_count: usize,
+    /// Only present for numeric columns with at least one value
+    pub numeric: Option<NumericStats>,
+    /// The most frequent values, only listed for non numeric columns
+    pub top_values: Vec<ValueCount>,
+    /// Equal width bins for numeric columns, the most frequent values for the other columns
+    pub histogram: Vec<HistogramBin>,
+}
+
+/// Calculate the statistics of a column
+pub fn column_stats(series: &Series) -> PolarsResult<ColumnStats> {
+    let mut stats = ColumnStats {
+        name: series.name().to_string(),
+        dtype: series.dtype().clone(),
+        count: series.len(),
+        null_count: series.null_count(),
+        distinct_count: series.drop_nulls().n_unique()?,
+        numeric: None,
+        top_values: Vec::new(),
+        histogram: Vec::new(),
+    };
+
+    if series.dtype().is_numeric() {
+        let values = series.cast(&DataType::Float64)?;
+        let values = values.f64()?;
+        if let (Some(min), Some(max), Some(mean), Some(median)) =
+            (values.min(), values.max(), values.mean(), values.median())
+        {
+            let mut quantiles = Vec::with_capacity(QUANTILES.len());
+            for &quantile in QUANTILES {
+                if let Some(value) = values.quantile(quantile, QuantileMethod::Linear)? {
+                    quantiles.push((quantile, value));
+                }
+            }
+
+            stats.histogram = histogram(values.iter().flatten(), min, max);
+            stats.numeric = Some(NumericStats {
+                min,
+                max,
+                mean,
+                median,
+                std_dev: values.std(1),
+                quantiles,
+            });
+        }
+    } else {
+        stats.top_values = valuefilter::series_distinct_values(series, TOP_VALUES)?.values;
+        stats.histogram = stats
+            .top_values
+            .iter()
+            .map(|value| HistogramBin {
+                label: value.value.clone().unwrap_or_else(|| "(null)".to_string()),
+                count: value.count,
+            })
+            .collect();
+    }
+
+    Ok(stats)
+}
+
+fn histogram(values: impl Iterator<Item = f64>, min: f64, max: f64) -> Vec<HistogramBin> {
+    let range = max - min;
+    let bin_count = if range > 0.0 { HISTOGRAM_BINS } else { 1 };
+    let bin_width = range / bin_count as f64;
+
+    let mut counts = vec![0; bin_count];
+    for value in values {
+        let bin = if range > 0.0 {
+            ((value - min) / bin_width) as usize
+        } else {
+            0
+        };
+        // The maximum is included in the last bin
+        counts[bin.min(bin_count - 1)] += 1;
+    }
+
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(index, count)| HistogramBin {
+            label: format_number(min + index as f64 * bin_width),
+            count,
+        })
+        .collect()
+}
+
+/// Format a statistic, whole numbers are shown without decimals
+pub fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.4}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+
+    #[test]
+    fn test_numeric_stats() {
+        let series = Series::new(
+            "value".into(),
+            [Some(1.0), Some(2.0), None, Some(3.0), Some(4.0), Some(10.0)],
+        );
+        let stats = column_stats(&series).unwrap();
+
+        assert_eq!(stats.count, 6);
+        assert_eq!(stats.null_count, 1);
+        assert_eq!(stats.distinct_count, 5);
+        assert!(stats.top_values.is_empty());
+
+        let numeric = stats.numeric.unwrap();
+        assert_eq!(numeric.min, 1.0);
+        assert_eq!(numeric.max, 10.0);
+        assert_eq!(numeric.mean, 4.0);
+        assert_eq!(numeric.median, 3.0);
+        assert_eq!(numeric.quantiles.len(), QUANTILES.len());
+
+        assert_eq!(stats.histogram.len(), HISTOGRAM_BINS);
+        assert_eq!(stats.histogram[0].count, 2);
+        assert_eq!(stats.histogram[HISTOGRAM_BINS - 1].count, 1);
+        assert_eq!(
+            stats.histogram.iter().map(|bin| bin.count).sum::<usize>(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_string_stats() {
+        let series = Series::new(
+            "city".into(),
+            [Some("Gent"), Some("Brussel"), None, Some("Gent")],
+        );
+        let stats = column_stats(&series).unwrap();
+
+        assert!(stats.numeric.is_none());
+        assert_eq!(stats.distinct_count, 2);
+        assert_eq!(stats.top_values[0].value, None);
+        assert_eq!(stats.top_values[1].value.as_deref(), Some("Gent"));
+        assert_eq!(stats.top_values[1].count, 2);
+        assert_eq!(stats.histogram.len(), 3);
+    }
+
+    #[test]
+    fn test_constant_column_histogram() {
+        let series = Series::new("value".into(), [5, 5, 5]);
+        let stats = column_stats(&series).unwrap();
+        assert_eq!(stats.histogram.len(), 1);
+        assert_eq!(stats.histogram[0].count, 3);
+        assert_eq!(stats.numeric.unwrap().std_dev, Some(0.0));
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(3.0), "3");
+        assert_eq!(format_number(-2.5), "-2.5000");
+        assert_eq!(format_number(1.0 / 3.0), "0.3333");
+    }
+}
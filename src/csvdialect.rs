@@ -0,0 +1,539 @@
+use anyhow::{Context, Result, bail};
+use encoding_rs::CoderResult;
+use polars::frame::DataFrame;
+use polars::prelude::{CsvEncoding, CsvParseOptions, CsvReadOptions, SerReader as _};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use crate::textencoding::{self, TextEncoding};
+
+/// The number of bytes at the start of the file that is used to detect the dialect
+const SAMPLE_SIZE: usize = 64 * 1024;
+/// The number of rows that is read for the preview of the options
+pub const PREVIEW_ROWS: usize = 20;
+/// The number of bytes that is transcoded for the preview of a file that is not UTF-8 encoded
+const PREVIEW_SIZE: usize = 1024 * 1024;
+
+pub const DELIMITERS: &[char] = &[',', ';', '\t', '|'];
+const QUOTES: &[char] = &['"', '\''];
+
+/// How a delimited text file is read
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    /// The character used to quote fields, quotes are not parsed when not set
+    #[serde(default)]
+    pub quote: Option<char>,
+    /// The line of the header, the lines before it are skipped. The file has no header when not set
+    #[serde(default)]
+    pub header_row: Option<usize>,
+    /// The number of rows that is skipped after the header, or at the start of a file without header
+    #[serde(default)]
+    pub skip_rows: usize,
+    /// Numbers use a comma as decimal separator, e.g. `3,14`
+    #[serde(default)]
+    pub decimal_comma: bool,
+    /// The character encoding, the text is transcoded to UTF-8 before it is parsed
+    #[serde(default)]
+    pub encoding: TextEncoding,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quote: Some('"'),
+            header_row: Some(0),
+            skip_rows: 0,
+            decimal_comma: false,
+            encoding: TextEncoding::UTF_8,
+        }
+    }
+}
+
+/// A record of the sample, `line` is the line on which the record starts
+struct Record {
+    line: usize,
+    fields: Vec<String>,
+}
+
+/// Split the text in records, line breaks inside quoted fields are part of the field
+fn split_records(text: &str, delimiter: char, quote: Option<char>) -> Vec<Record> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let (mut line, mut record_line) = (0, 0);
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if Some(c) == quote => {
+                if in_quotes && chars.peek() == Some(&c) {
+                    field.push(c);
+                    chars.next();
+                } else {
+                    in_quotes = !in_quotes;
+                }
+            }
+            '\n' if !in_quotes => {
+                fields.push(std::mem::take(&mut field));
+                records.push(Record {
+                    line: record_line,
+                    fields: std::mem::take(&mut fields),
+                });
+                line += 1;
+                record_line = line;
+            }
+            '\r' if !in_quotes => {}
+            c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut field)),
+            c => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push(Record {
+            line: record_line,
+            fields,
+        });
+    }
+
+    records.retain(|record| record.fields.iter().any(|field| !field.trim().is_empty()));
+    records
+}
+
+/// The most common field count of the records and the number of records that have it
+fn dominant_field_count(records: &[Record]) -> (usize, usize) {
+    let mut counts = std::collections::HashMap::<usize, usize>::new();
+    for record in records {
+        *counts.entry(record.fields.len()).or_default() += 1;
+    }
+
+    counts
+        .into_iter()
+        .max_by_key(|&(fields, records)| (records, fields))
+        .unwrap_or_default()
+}
+
+fn is_number(text: &str, decimal_comma: bool) -> bool {
+    let text = text.trim();
+    let text = if decimal_comma {
+        text.replacen(',', ".", 1)
+    } else {
+        text.to_string()
+    };
+
+    !text.is_empty() && text.parse::<f64>().is_ok()
+}
+
+/// Numbers like `3,14` or `3.14`, a fraction of three digits can be a thousands separator
+/// so those numbers are ambiguous and do not count
+fn is_decimal_number(text: &str, separator: char) -> bool {
+    let text = text.trim().trim_start_matches(['-', '+']);
+    match text.split_once(separator) {
+        Some((integer, fraction)) => {
+            !integer.is_empty()
+                && integer.chars().all(|c| c.is_ascii_digit())
+                && !fraction.is_empty()
+                && fraction.len() != 3
+                && fraction.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// The quote character that encloses the most fields
+fn detect_quote(text: &str, delimiter: char) -> char {
+    let quoted_fields = |quote: char| {
+        text.lines()
+            .flat_map(|line| line.split(delimiter))
+            .filter(|field| {
+                let field = field.trim();
+                field.len() >= 2 && field.starts_with(quote) && field.ends_with(quote)
+            })
+            .count()
+    };
+
+    QUOTES
+        .iter()
+        .copied()
+        .max_by_key(|&quote| (quoted_fields(quote), quote == '"'))
+        .unwrap_or('"')
+}
+
+/// The first record is a header when it has no numbers, or when it has text in a column that
+/// contains numbers in the other records
+fn has_header(records: &[Record], decimal_comma: bool) -> bool {
+    let Some((first, rest)) = records.split_first() else {
+        return true;
+    };
+
+    if !first
+        .fields
+        .iter()
+        .any(|field| is_number(field, decimal_comma))
+    {
+        return true;
+    }
+
+    (0..first.fields.len()).any(|col_ix| {
+        let numeric_column = rest.iter().all(|record| {
+            record
+                .fields
+                .get(col_ix)
+                .is_none_or(|field| field.trim().is_empty() || is_number(field, decimal_comma))
+        });
+        numeric_column && !rest.is_empty() && !is_number(&first.fields[col_ix], decimal_comma)
+    })
+}
+
+/// Detect the dialect of the sample from the start of a delimited text file
+/// The encoding is detected as well, unless it is provided
+pub fn sniff(sample: &[u8], encoding: Option<TextEncoding>) -> CsvOptions {
+    let complete = sample.len() < SAMPLE_SIZE;
+    let encoding = encoding.unwrap_or_else(|| textencoding::detect(sample, complete));
+
+    let text = encoding.decode(sample);
+    // The last line of the sample is incomplete when the file is larger than the sample
+    let text = match text.rfind('\n') {
+        Some(end) if !complete => &text[..end],
+        _ => &text[..],
+    };
+
+    // The delimiter that splits the most records into the same number of fields
+    let (delimiter, records, field_count) = DELIMITERS
+        .iter()
+        .map(|&delimiter| {
+            let records = split_records(text, delimiter, Some(detect_quote(text, delimiter)));
+            let (field_count, record_count) = dominant_field_count(&records);
+            (delimiter, records, field_count, record_count)
+        })
+        .filter(|(_, _, field_count, _)| *field_count > 1)
+        .max_by_key(|(_, _, field_count, record_count)| (*record_count, *field_count))
+        .map(|(delimiter, records, field_count, _)| (delimiter, records, field_count))
+        .unwrap_or_else(|| (',', split_records(text, ',', Some('"')), 1));
+
+    // Preamble lines, e.g. comments or a title, do not have the number of fields of the table
+    let records: Vec<Record> = records
+        .into_iter()
+        .skip_while(|record| record.fields.len() != field_count)
+        .collect();
+    let first_line = records
+        .first()
+        .map(|record| record.line)
+        .unwrap_or_default();
+
+    let data_fields = || records.iter().skip(1).flat_map(|record| &record.fields);
+    let decimal_comma = delimiter != ','
+        && data_fields().any(|field| is_decimal_number(field, ','))
+        && !data_fields().any(|field| is_decimal_number(field, '.'));
+
+    let header = has_header(&records, decimal_comma);
+    CsvOptions {
+        delimiter,
+        quote: Some(detect_quote(text, delimiter)),
+        header_row: header.then_some(first_line),
+        skip_rows: if header { 0 } else { first_line },
+        decimal_comma,
+        encoding,
+    }
+}
+
+/// Read the start of the file and detect its dialect, the encoding is detected when not provided
+pub fn sniff_file(path: &Path, encoding: Option<TextEncoding>) -> Result<CsvOptions> {
+    let mut sample = Vec::with_capacity(SAMPLE_SIZE);
+    File::open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?
+        .take(SAMPLE_SIZE as u64)
+        .read_to_end(&mut sample)?;
+
+    Ok(sniff(&sample, encoding))
+}
+
+/// Transcode the file to UTF-8 in chunks, so the undecoded file is never completely in memory
+/// When a limit is given, only the complete lines of (about) the first `limit` bytes are transcoded
+fn transcode(path: &Path, encoding: TextEncoding, limit: Option<usize>) -> Result<String> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut decoder = encoding.new_decoder();
+    let mut buffer = vec![0; SAMPLE_SIZE];
+    let mut text = String::new();
+    let mut read = 0;
+    loop {
+        let len = file
+            .read(&mut buffer)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        read += len;
+        let last = len == 0;
+
+        let capacity = decoder
+            .max_utf8_buffer_length(len)
+            .context("The file is too large to transcode")?;
+        text.reserve(capacity);
+        let (result, _, _) = decoder.decode_to_string(&buffer[..len], &mut text, last);
+        debug_assert_eq!(result, CoderResult::InputEmpty);
+
+        if last {
+            return Ok(text);
+        }
+        if limit.is_some_and(|limit| read >= limit) {
+            // The last line can be incomplete, it is left out
+            if let Some(end) = text.rfind('\n') {
+                text.truncate(end + 1);
+            }
+            return Ok(text);
+        }
+    }
+}
+
+/// Whether the complete file is valid UTF-8, the file is validated in chunks
+fn is_utf8_file(path: &Path) -> Result<bool> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut buffer = vec![0; SAMPLE_SIZE];
+    // The start of a character that continues in the next chunk
+    let mut pending = 0;
+    loop {
+        let len = file
+            .read(&mut buffer[pending..])
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let end = pending + len;
+        match std::str::from_utf8(&buffer[..end]) {
+            Ok(_) if len == 0 => return Ok(true),
+            Ok(_) => pending = 0,
+            Err(err) if err.error_len().is_none() && len > 0 => {
+                buffer.copy_within(err.valid_up_to()..end, 0);
+                pending = end - err.valid_up_to();
+            }
+            Err(_) => return Ok(false),
+        }
+    }
+}
+
+/// Detect the encoding of the complete file when it is not valid UTF-8, `None` when it is
+/// The start of a file can be ASCII while the rest of the file is in another encoding
+pub fn detect_non_utf8_encoding(path: &Path) -> Result<Option<TextEncoding>> {
+    if is_utf8_file(path)? {
+        return Ok(None);
+    }
+
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let encoding = textencoding::detect_reader(file)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(Some(encoding))
+}
+
+/// Read the delimited text file, at most `n_rows` rows are read when a limit is given
+/// UTF-8 text is parsed strictly, invalid UTF-8 is an error instead of replaced characters
+pub fn read_csv(path: &Path, options: &CsvOptions, n_rows: Option<usize>) -> Result<DataFrame> {
+    let ascii = |c: char, name: &str| -> Result<u8> {
+        if !c.is_ascii() {
+            bail!("The {name} must be an ASCII character, got '{c}'");
+        }
+        Ok(c as u8)
+    };
+
+    let delimiter = ascii(options.delimiter, "delimiter")?;
+    let quote = options
+        .quote
+        .map(|quote| ascii(quote, "quote character"))
+        .transpose()?;
+    if options.decimal_comma && options.delimiter == ',' {
+        bail!("A comma can not be both the delimiter and the decimal separator");
+    }
+
+    let parse_options = CsvParseOptions::default()
+        .with_separator(delimiter)
+        .with_quote_char(quote)
+        .with_decimal_comma(options.decimal_comma)
+        .with_encoding(CsvEncoding::Utf8)
+        .with_missing_is_null(true)
+        .with_truncate_ragged_lines(true)
+        .with_try_parse_dates(true);
+
+    let read_options = CsvReadOptions::default()
+        .with_parse_options(parse_options)
+        .with_has_header(options.header_row.is_some())
+        .with_n_rows(n_rows);
+    let read_options = match options.header_row {
+        Some(line) => read_options
+            .with_skip_lines(line)
+            .with_skip_rows_after_header(options.skip_rows),
+        None => read_options.with_skip_rows(options.skip_rows),
+    };
+
+    if options.encoding == TextEncoding::UTF_8 {
+        return Ok(read_options
+            .try_into_reader_with_file_path(Some(path.to_path_buf()))?
+            .finish()?);
+    }
+
+    // Other encodings are transcoded in memory, the parser only reads UTF-8
+    // A preview only needs the first rows, so only the start of the file is transcoded
+    let limit = n_rows.map(|_| PREVIEW_SIZE);
+    let text = transcode(path, options.encoding, limit)?;
+    Ok(read_options
+        .into_reader_with_file_handle(Cursor::new(text.into_bytes()))
+        .finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::temp_path;
+    use polars::prelude::*;
+
+    #[test]
+    fn test_sniff_comma_delimited() {
+        let options = sniff(b"name,age\nAlice,31\n\"O'Brien, Bob\",42\n", None);
+        assert_eq!(options, CsvOptions::default());
+    }
+
+    #[test]
+    fn test_sniff_semicolon_with_decimal_comma() {
+        let options = sniff(b"name;score;total\nAlice;1,5;1.234\nBob;2,25;12\n", None);
+        assert_eq!(options.delimiter, ';');
+        assert!(options.decimal_comma);
+        assert_eq!(options.header_row, Some(0));
+    }
+
+    #[test]
+    fn test_sniff_preamble_and_quoted_newlines() {
+        let options = sniff(
+            b"# Export of 2024-01-01\n# Generated by the ERP\nid\tnote\n1\t\"first\nline\"\n2\tsecond\n",
+            None,
+        );
+        assert_eq!(options.delimiter, '\t');
+        assert_eq!(options.header_row, Some(2));
+    }
+
+    #[test]
+    fn test_sniff_without_header() {
+        let options = sniff(b"1|2.5|a\n2|3.5|b\n3|4.5|c\n", None);
+        assert_eq!(options.delimiter, '|');
+        assert_eq!(options.header_row, None);
+        assert!(!options.decimal_comma);
+    }
+
+    #[test]
+    fn test_sniff_encoding() {
+        let (sample, _, _) = encoding_rs::WINDOWS_1252
+            .encode("naam;stad\nZoë;Liège\nFrançois;Zürich\nRenée;Besançon\n");
+        let options = sniff(&sample, None);
+        assert_eq!(options.encoding.name(), "windows-1252");
+        assert_eq!(options.delimiter, ';');
+
+        let latin1 = TextEncoding::for_label("latin1");
+        assert_eq!(sniff(b"a,b\n", latin1).encoding, latin1.unwrap());
+    }
+
+    #[test]
+    fn test_read_csv_with_options() {
+        let path = temp_path("dialect.csv");
+        std::fs::write(
+            &path,
+            "Report title\nname;score\nunits;points\nAlice;1,5\nBob;2,25\n",
+        )
+        .unwrap();
+
+        let options = sniff_file(&path, None).unwrap();
+        assert_eq!(options.header_row, Some(1));
+
+        let options = CsvOptions {
+            skip_rows: 1,
+            ..options
+        };
+        let data = read_csv(&path, &options, None).unwrap();
+        assert_eq!(data.shape(), (2, 2));
+        assert_eq!(
+            data.column("score").unwrap().get(1).unwrap(),
+            AnyValue::Float64(2.25)
+        );
+
+        let preview = read_csv(&path, &options, Some(1)).unwrap();
+        assert_eq!(preview.height(), 1);
+
+        let invalid = CsvOptions {
+            delimiter: ',',
+            decimal_comma: true,
+            ..options
+        };
+        assert!(read_csv(&path, &invalid, None).is_err());
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_transcode_prefix() {
+        let path = temp_path("transcode.csv");
+        let text = "city,count\n".to_string() + &"Liège,1\n".repeat(20_000);
+        let (windows_1252, _, _) = encoding_rs::WINDOWS_1252.encode(&text);
+        std::fs::write(&path, windows_1252).unwrap();
+        let encoding = TextEncoding::for_label("windows-1252").unwrap();
+
+        assert_eq!(transcode(&path, encoding, None).unwrap(), text);
+
+        let prefix = transcode(&path, encoding, Some(1)).unwrap();
+        assert!(prefix.len() < text.len());
+        assert!(prefix.ends_with("Liège,1\n"));
+        assert!(text.starts_with(&prefix));
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_invalid_utf8_after_sample() {
+        let path = temp_path("late_encoding.csv");
+        let mut bytes = "name,city\n".to_string().into_bytes();
+        bytes.extend("Alice,Gent\n".repeat(10_000).into_bytes());
+        let (windows_1252, _, _) =
+            encoding_rs::WINDOWS_1252.encode("Zoë,Liège\nFrançois,Besançon\nJürgen,Zürich\n");
+        bytes.extend_from_slice(&windows_1252);
+        std::fs::write(&path, bytes).unwrap();
+
+        let options = sniff_file(&path, None).unwrap();
+        assert_eq!(options.encoding, TextEncoding::UTF_8);
+        assert!(read_csv(&path, &options, None).is_err());
+
+        let encoding = detect_non_utf8_encoding(&path).unwrap();
+        assert_eq!(encoding.map(TextEncoding::name), Some("windows-1252"));
+        let options = sniff_file(&path, encoding).unwrap();
+        let data = read_csv(&path, &options, None).unwrap();
+        assert_eq!(data.height(), 10_003);
+        assert_eq!(
+            data.column("city").unwrap().get(10_000).unwrap(),
+            AnyValue::String("Liège")
+        );
+
+        std::fs::write(&path, "name\nZoë\n").unwrap();
+        assert_eq!(detect_non_utf8_encoding(&path).unwrap(), None);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_read_transcoded_csv() {
+        let path = temp_path("encoding.csv");
+        let text = "name,city\nZoë,Liège\nFrançois,Besançon\n";
+
+        let mut utf16 = vec![0xff, 0xfe];
+        utf16.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        let (windows_1252, _, _) = encoding_rs::WINDOWS_1252.encode(text);
+
+        for bytes in [utf16, windows_1252.into_owned()] {
+            std::fs::write(&path, bytes).unwrap();
+            let options = sniff_file(&path, None).unwrap();
+            let data = read_csv(&path, &options, None).unwrap();
+            assert_eq!(
+                data.column("city").unwrap().get(0).unwrap(),
+                AnyValue::String("Liège")
+            );
+        }
+        std::fs::remove_file(path).ok();
+    }
+}
@@ -0,0 +1,351 @@
+use gpui::*;
+use gpui_component::{
+    ActiveTheme, Selectable, Sizable, StyledExt,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    spinner::Spinner,
+    v_flex,
+};
+use polars::frame::DataFrame;
+use polars::prelude::AnyValue;
+use std::path::PathBuf;
+
+use crate::csvdialect::{self, CsvOptions, DELIMITERS, PREVIEW_ROWS};
+use crate::textencoding::TextEncoding;
+use crate::utils;
+
+/// The number of columns that is shown in the preview
+const PREVIEW_COLUMNS: usize = 30;
+
+pub enum CsvDialogEvent {
+    /// Reopen the file with the chosen options
+    Apply(CsvOptions),
+    Cancel,
+}
+
+/// Choose the read options of a delimited text file, with a live preview of the first rows
+pub struct CsvOptionsDialog {
+    path: PathBuf,
+    options: CsvOptions,
+    header_row_input: Entity<InputState>,
+    skip_rows_input: Entity<InputState>,
+    /// `None` while the preview is being read
+    preview: Option<Result<DataFrame, SharedString>>,
+    _preview_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl EventEmitter<CsvDialogEvent> for CsvOptionsDialog {}
+
+fn delimiter_name(delimiter: char) -> &'static str {
+    match delimiter {
+        ',' => "Comma",
+        ';' => "Semicolon",
+        '\t' => "Tab",
+        '|' => "Pipe",
+        _ => "Other",
+    }
+}
+
+impl CsvOptionsDialog {
+    pub fn new(
+        path: PathBuf,
+        options: CsvOptions,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        // Rows are numbered from 1 in the dialog
+        let header_row = options
+            .header_row
+            .map(|line| (line + 1).to_string())
+            .unwrap_or_default();
+        let header_row_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("No header")
+                .default_value(header_row)
+        });
+        let skip_rows_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("0")
+                .default_value(options.skip_rows.to_string())
+        });
+        let _subscriptions = vec![
+            cx.subscribe_in(&header_row_input, window, Self::on_input_event),
+            cx.subscribe_in(&skip_rows_input, window, Self::on_input_event),
+        ];
+
+        let mut dialog = Self {
+            path,
+            options,
+            header_row_input,
+            skip_rows_input,
+            preview: None,
+            _preview_task: Task::ready(()),
+            _subscriptions,
+        };
+        dialog.update_preview(cx);
+        dialog
+    }
+
+    fn on_input_event(
+        &mut self,
+        _: &Entity<InputState>,
+        event: &InputEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Change = event {
+            let number = |input: &Entity<InputState>| input.read(cx).value().trim().parse().ok();
+            let header_row: Option<usize> = number(&self.header_row_input);
+            let skip_rows: Option<usize> = number(&self.skip_rows_input);
+
+            self.set_options(
+                CsvOptions {
+                    header_row: header_row.and_then(|row| row.checked_sub(1)),
+                    skip_rows: skip_rows.unwrap_or_default(),
+                    ..self.options.clone()
+                },
+                cx,
+            );
+        }
+    }
+
+    fn set_options(&mut self, options: CsvOptions, cx: &mut Context<Self>) {
+        if options != self.options {
+            self.options = options;
+            self.update_preview(cx);
+        }
+    }
+
+    /// Read the first rows of the file with the current options on the background executor
+    /// Replacing the task cancels the preview of the previous options
+    fn update_preview(&mut self, cx: &mut Context<Self>) {
+        let path = self.path.clone();
+        let options = self.options.clone();
+
+        self.preview = None;
+        self._preview_task = cx.spawn(async move |this, cx| {
+            let preview = cx
+                .background_executor()
+                .spawn(async move { csvdialect::read_csv(&path, &options, Some(PREVIEW_ROWS)) })
+                .await;
+
+            let _ = this.update(cx, |this, cx| {
+                this.preview = Some(preview.map_err(|err| {
+                    utils::error_message("Failed to read the file with these options", err)
+                }));
+                cx.notify();
+            });
+        });
+        cx.notify();
+    }
+
+    fn render_choices<T: Copy + PartialEq + 'static>(
+        &self,
+        id: &'static str,
+        label: &'static str,
+        choices: Vec<(T, SharedString)>,
+        selected: T,
+        apply: fn(&mut CsvOptions, T),
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        h_flex()
+            .gap_1()
+            .child(div().w(px(120.)).child(label))
+            .children(choices.into_iter().enumerate().map(|(ix, (value, name))| {
+                Button::new((id, ix))
+                    .label(name)
+                    .small()
+                    .ghost()
+                    .selected(value == selected)
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        let mut options = this.options.clone();
+                        apply(&mut options, value);
+                        this.set_options(options, cx);
+                    }))
+            }))
+    }
+
+    fn render_preview(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let content = match &self.preview {
+            None => h_flex()
+                .justify_center()
+                .p_4()
+                .child(Spinner::new())
+                .into_any_element(),
+            Some(Err(message)) => div()
+                .p_2()
+                .text_color(cx.theme().danger)
+                .child(message.clone())
+                .into_any_element(),
+            Some(Ok(data)) => {
+                let columns = &data.get_columns()[..data.width().min(PREVIEW_COLUMNS)];
+                let cell = || {
+                    div()
+                        .w(px(120.))
+                        .flex_none()
+                        .px_1()
+                        .overflow_hidden()
+                        .text_ellipsis()
+                        .whitespace_nowrap()
+                };
+
+                v_flex()
+                    .child(
+                        h_flex()
+                            .font_semibold()
+                            .border_b_1()
+                            .border_color(cx.theme().border)
+                            .children(columns.iter().map(|column| {
+                                cell().child(format!("{} ({})", column.name(), column.dtype()))
+                            })),
+                    )
+                    .children((0..data.height()).map(|row_ix| {
+                        h_flex().children(columns.iter().map(move |column| {
+                            let text = match column.get(row_ix) {
+                                Ok(AnyValue::Null) | Err(_) => String::new(),
+                                Ok(AnyValue::String(text)) => text.to_string(),
+                                Ok(value) => value.to_string(),
+                            };
+                            cell().child(text)
+                        }))
+                    }))
+                    .into_any_element()
+            }
+        };
+
+        div()
+            .id("csv-preview")
+            .flex_1()
+            .min_h(px(120.))
+            .border_1()
+            .border_color(cx.theme().border)
+            .rounded(cx.theme().radius)
+            .overflow_scroll()
+            .child(content)
+    }
+}
+
+impl Render for CsvOptionsDialog {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let delimiters = DELIMITERS
+            .iter()
+            .map(|&delimiter| (delimiter, delimiter_name(delimiter).into()))
+            .collect();
+        let quotes = vec![
+            (Some('"'), "\"".into()),
+            (Some('\''), "'".into()),
+            (None, "None".into()),
+        ];
+        let decimal_separators = vec![(false, "Point".into()), (true, "Comma".into())];
+        // A detected encoding that is not one of the common encodings is offered as well
+        let mut encodings: Vec<(TextEncoding, SharedString)> = TextEncoding::COMMON
+            .iter()
+            .map(|&encoding| (encoding, encoding.name().into()))
+            .collect();
+        if !TextEncoding::COMMON.contains(&self.options.encoding) {
+            let encoding = self.options.encoding;
+            encodings.push((encoding, encoding.name().into()));
+        }
+        let has_header = self.options.header_row.is_some();
+
+        v_flex()
+            .gap_2()
+            .p_2()
+            .h(px(420.))
+            .text_sm()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(div().font_semibold().child(format!(
+                "Reopen {} with options",
+                self.path
+                    .file_name()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or_default()
+            )))
+            .child(self.render_choices(
+                "csv-delimiter",
+                "Delimiter",
+                delimiters,
+                self.options.delimiter,
+                |options, delimiter| options.delimiter = delimiter,
+                cx,
+            ))
+            .child(self.render_choices(
+                "csv-quote",
+                "Quote",
+                quotes,
+                self.options.quote,
+                |options, quote| options.quote = quote,
+                cx,
+            ))
+            .child(self.render_choices(
+                "csv-decimal",
+                "Decimal separator",
+                decimal_separators,
+                self.options.decimal_comma,
+                |options, decimal_comma| options.decimal_comma = decimal_comma,
+                cx,
+            ))
+            .child(self.render_choices(
+                "csv-encoding",
+                "Encoding",
+                encodings,
+                self.options.encoding,
+                |options, encoding| options.encoding = encoding,
+                cx,
+            ))
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(div().w(px(120.)).child("Header row"))
+                    .child(
+                        div()
+                            .w(px(100.))
+                            .child(Input::new(&self.header_row_input).small()),
+                    )
+                    .child(div().w(px(20.)))
+                    .child(div().w(px(120.)).child("Rows to skip"))
+                    .child(
+                        div()
+                            .w(px(100.))
+                            .child(Input::new(&self.skip_rows_input).small()),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(if has_header {
+                                "The lines before the header are skipped, rows are skipped after the header"
+                            } else {
+                                "Rows are skipped at the start of the file"
+                            }),
+                    ),
+            )
+            .child(self.render_preview(cx))
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new("apply-csv-options")
+                            .label("Reopen")
+                            .primary()
+                            .small()
+                            .disabled(!matches!(self.preview, Some(Ok(_))))
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                cx.emit(CsvDialogEvent::Apply(this.options.clone()));
+                            })),
+                    )
+                    .child(
+                        Button::new("cancel-csv-options")
+                            .label("Cancel")
+                            .ghost()
+                            .small()
+                            .on_click(cx.listener(|_, _, _, cx| {
+                                cx.emit(CsvDialogEvent::Cancel);
+                            })),
+                    ),
+            )
+    }
+}
@@ -0,0 +1,133 @@
+use gpui::Global;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::appconfig;
+use crate::csvdialect::CsvOptions;
+
+const CSV_SETTINGS_NAME: &str = "csv_options.toml";
+/// The options of the least recently reopened files are forgotten
+const MAX_CSV_FILES: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CsvFileOptions {
+    path: PathBuf,
+    options: CsvOptions,
+}
+
+/// The read options that were chosen for delimited text files, files without options are sniffed
+/// The most recently chosen options first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CsvSettings {
+    #[serde(default, rename = "file")]
+    files: Vec<CsvFileOptions>,
+    #[serde(skip)]
+    storage_path: Option<PathBuf>,
+}
+
+impl Global for CsvSettings {}
+
+impl CsvSettings {
+    /// Load the csv options that are stored next to the default config file
+    pub fn load() -> Self {
+        let (mut settings, storage_path) =
+            appconfig::load_stored::<Self>(CSV_SETTINGS_NAME, "csv options");
+        settings.storage_path = storage_path;
+        settings
+    }
+
+    pub fn save(&self) {
+        if let Some(path) = &self.storage_path
+            && let Err(err) = appconfig::save_toml(self, path, "csv options")
+        {
+            log::warn!("{err:#}");
+        }
+    }
+
+    /// The options that were chosen for the file
+    pub fn get(&self, path: &Path) -> Option<&CsvOptions> {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.files
+            .iter()
+            .find(|file| file.path == path)
+            .map(|file| &file.options)
+    }
+
+    /// Remember the options for the file, replacing the previous options
+    pub fn set(&mut self, path: &Path, options: CsvOptions) {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.files.retain(|file| file.path != path);
+        self.files.insert(0, CsvFileOptions { path, options });
+        self.files.truncate(MAX_CSV_FILES);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::temp_path;
+
+    #[test]
+    fn test_set_replaces_options() {
+        let mut settings = CsvSettings::default();
+        let path = Path::new("/nonexistent/a.csv");
+        assert_eq!(settings.get(path), None);
+
+        settings.set(path, CsvOptions::default());
+        let options = CsvOptions {
+            delimiter: ';',
+            ..Default::default()
+        };
+        settings.set(path, options.clone());
+
+        assert_eq!(settings.files.len(), 1);
+        assert_eq!(settings.get(path), Some(&options));
+    }
+
+    #[test]
+    fn test_number_of_files_is_limited() {
+        let mut settings = CsvSettings::default();
+        for i in 0..MAX_CSV_FILES + 5 {
+            settings.set(
+                Path::new(&format!("/nonexistent/{i}.csv")),
+                CsvOptions::default(),
+            );
+        }
+
+        assert_eq!(settings.files.len(), MAX_CSV_FILES);
+        assert!(settings.get(Path::new("/nonexistent/0.csv")).is_none());
+        assert!(
+            settings
+                .get(Path::new(&format!(
+                    "/nonexistent/{}.csv",
+                    MAX_CSV_FILES + 4
+                )))
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let path = temp_path("csv_options.toml");
+
+        let mut settings = CsvSettings::default();
+        settings.set(
+            Path::new("/nonexistent/a.csv"),
+            CsvOptions {
+                delimiter: '\t',
+                quote: None,
+                header_row: None,
+                skip_rows: 2,
+                decimal_comma: true,
+                ..Default::default()
+            },
+        );
+        appconfig::save_toml(&settings, &path, "csv options").unwrap();
+
+        let loaded: CsvSettings = appconfig::load_toml(&path, "csv options").unwrap();
+        assert_eq!(loaded.files, settings.files);
+
+        fs::remove_file(path).ok();
+    }
+}
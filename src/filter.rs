@@ -0,0 +1,304 @@
+use polars::{
+    frame::DataFrame,
+    prelude::{DataType, Expr, IntoLazy, PolarsResult, col, lit},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::filterquery::FilterQuery;
+
+/// How the filter text is matched against the column values
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterOptions {
+    /// The filter text is a regular expression
+    pub regex: bool,
+    pub case_sensitive: bool,
+    /// The filter text has to match the complete value instead of a part of it
+    pub whole_value: bool,
+}
+
+/// A filter on the values of a single column
+/// The filter text is parsed as a [`FilterQuery`] unless it is a regular expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnFilter {
+    pub column: String,
+    dtype: DataType,
+    text: String,
+    options: FilterOptions,
+    error: Option<String>,
+}
+
+impl ColumnFilter {
+    pub fn new(column: impl Into<String>, dtype: DataType, text: impl Into<String>) -> Self {
+        Self::with_options(column, dtype, text, FilterOptions::default())
+    }
+
+    pub fn with_options(
+        column: impl Into<String>,
+        dtype: DataType,
+        text: impl Into<String>,
+        options: FilterOptions,
+    ) -> Self {
+        let mut filter = Self {
+            column: column.into(),
+            dtype,
+            text: text.into(),
+            options,
+            error: None,
+        };
+        filter.validate();
+        filter
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn options(&self) -> FilterOptions {
+        self.options
+    }
+
+    /// The reason why the filter text is invalid, invalid filters are not applied
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.validate();
+    }
+
+    pub fn set_options(&mut self, options: FilterOptions) {
+        self.options = options;
+        self.validate();
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.text.is_empty() && self.error.is_none()
+    }
+
+    fn regex_pattern(&self) -> String {
+        let mut pattern = if self.options.whole_value {
+            format!("^(?:{})$", self.text)
+        } else {
+            self.text.clone()
+        };
+
+        if !self.options.case_sensitive {
+            pattern.insert_str(0, "(?i)");
+        }
+
+        pattern
+    }
+
+    fn validate(&mut self) {
+        self.error = if self.text.is_empty() {
+            None
+        } else {
+            self.expr().err()
+        };
+    }
+
+    fn expr(&self) -> Result<Expr, String> {
+        let value = col(self.column.as_str());
+        if self.options.regex {
+            let pattern = self.regex_pattern();
+            regex::Regex::new(&pattern).map_err(|err| err.to_string())?;
+            return Ok(value
+                .cast(DataType::String)
+                .str()
+                .contains(lit(pattern), true));
+        }
+
+        FilterQuery::parse(&self.text).compile(value, &self.dtype, self.options)
+    }
+}
+
+/// Keep the rows that match all the active filters
+pub fn apply_filters(data: DataFrame, filters: &[ColumnFilter]) -> PolarsResult<DataFrame> {
+    let mut active_filters = filters
+        .iter()
+        .filter(|filter| filter.is_active())
+        .filter_map(|filter| filter.expr().ok())
+        .peekable();
+    if active_filters.peek().is_none() {
+        return Ok(data);
+    }
+
+    let mut lazy_df = data.lazy();
+    for filter_expr in active_filters {
+        lazy_df = lazy_df.filter(filter_expr);
+    }
+
+    lazy_df.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::people;
+
+    fn filter(column: &str, text: &str) -> ColumnFilter {
+        filter_with_options(column, text, FilterOptions::default())
+    }
+
+    fn filter_with_options(column: &str, text: &str, options: FilterOptions) -> ColumnFilter {
+        let dtype = people().schema().get(column).cloned().unwrap();
+        ColumnFilter::with_options(column, dtype, text, options)
+    }
+
+    fn names(df: &DataFrame) -> Vec<String> {
+        df.column("name")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .map(|name| name.unwrap_or_default().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_no_filters_keeps_all_rows() {
+        let filtered = apply_filters(people(), &[]).unwrap();
+        assert_eq!(filtered.height(), 5);
+    }
+
+    #[test]
+    fn test_filter_is_case_insensitive() {
+        let filtered = apply_filters(people(), &[filter("name", "ALI")]).unwrap();
+        assert_eq!(names(&filtered), ["Alice", "alicia"]);
+    }
+
+    #[test]
+    fn test_filter_is_literal() {
+        let filtered = apply_filters(people(), &[filter("name", "a.i")]).unwrap();
+        assert_eq!(filtered.height(), 0);
+
+        // Characters with a special meaning in a regex do not cause an error
+        let filtered = apply_filters(people(), &[filter("name", "(")]).unwrap();
+        assert_eq!(filtered.height(), 0);
+    }
+
+    #[test]
+    fn test_filter_numeric_column() {
+        let filtered = apply_filters(people(), &[filter("age", "31")]).unwrap();
+        assert_eq!(names(&filtered), ["Alice", "Charlie"]);
+    }
+
+    #[test]
+    fn test_filters_are_combined() {
+        let filters = [filter("age", "31"), filter("city", "ge")];
+        let filtered = apply_filters(people(), &filters).unwrap();
+        assert_eq!(names(&filtered), ["Alice"]);
+    }
+
+    #[test]
+    fn test_filter_case_sensitive() {
+        let options = FilterOptions {
+            case_sensitive: true,
+            ..Default::default()
+        };
+        let filter = filter_with_options("name", "ali", options);
+        let filtered = apply_filters(people(), &[filter]).unwrap();
+        assert_eq!(names(&filtered), ["alicia"]);
+    }
+
+    #[test]
+    fn test_filter_whole_value() {
+        let options = FilterOptions {
+            whole_value: true,
+            ..Default::default()
+        };
+        let filter = filter_with_options("name", "alice", options);
+        let filtered = apply_filters(people(), &[filter]).unwrap();
+        assert_eq!(names(&filtered), ["Alice"]);
+    }
+
+    #[test]
+    fn test_filter_regex() {
+        let options = FilterOptions {
+            regex: true,
+            ..Default::default()
+        };
+        let filter = filter_with_options("name", "^(ali|bo)", options);
+        let filtered = apply_filters(people(), &[filter]).unwrap();
+        assert_eq!(names(&filtered), ["Alice", "Bob", "alicia"]);
+
+        let options = FilterOptions {
+            regex: true,
+            case_sensitive: true,
+            whole_value: true,
+        };
+        let filter = filter_with_options("name", "[A-Z][a-z]+e", options);
+        let filtered = apply_filters(people(), &[filter]).unwrap();
+        assert_eq!(names(&filtered), ["Alice", "Charlie"]);
+    }
+
+    #[test]
+    fn test_invalid_regex_is_not_applied() {
+        let options = FilterOptions {
+            regex: true,
+            ..Default::default()
+        };
+        let filter = filter_with_options("name", "(ali", options);
+        assert!(filter.error().is_some());
+        assert!(!filter.is_active());
+
+        let filtered = apply_filters(people(), &[filter]).unwrap();
+        assert_eq!(filtered.height(), 5);
+    }
+
+    #[test]
+    fn test_filter_comparisons() {
+        let filtered = apply_filters(people(), &[filter("age", "> 30")]).unwrap();
+        assert_eq!(names(&filtered), ["Alice", "Bob", "Charlie"]);
+
+        let filtered = apply_filters(people(), &[filter("age", "<=31")]).unwrap();
+        assert_eq!(
+            names(&filtered),
+            ["Alice", "alicia", "Charlie", "O'Brien, Dave"]
+        );
+
+        let filtered = apply_filters(people(), &[filter("age", "!= 31")]).unwrap();
+        assert_eq!(names(&filtered), ["Bob", "alicia", "O'Brien, Dave"]);
+
+        let filtered = apply_filters(people(), &[filter("age", "26..42")]).unwrap();
+        assert_eq!(names(&filtered), ["Alice", "Bob", "Charlie"]);
+
+        let filtered = apply_filters(people(), &[filter("name", "= alice")]).unwrap();
+        assert_eq!(names(&filtered), ["Alice"]);
+
+        let filtered = apply_filters(people(), &[filter("name", "!ali")]).unwrap();
+        assert_eq!(names(&filtered), ["Bob", "Charlie", "O'Brien, Dave"]);
+    }
+
+    #[test]
+    fn test_filter_null() {
+        let filtered = apply_filters(people(), &[filter("city", "null")]).unwrap();
+        assert_eq!(names(&filtered), ["alicia"]);
+
+        let filtered = apply_filters(people(), &[filter("city", "!null")]).unwrap();
+        assert_eq!(
+            names(&filtered),
+            ["Alice", "Bob", "Charlie", "O'Brien, Dave"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_comparison_is_not_applied() {
+        let filter = filter("age", "> abc");
+        assert!(filter.error().is_some());
+
+        let filtered = apply_filters(people(), &[filter]).unwrap();
+        assert_eq!(filtered.height(), 5);
+    }
+
+    #[test]
+    fn test_filter_skips_null_values() {
+        let filtered = apply_filters(people(), &[filter("city", "e")]).unwrap();
+        assert_eq!(
+            names(&filtered),
+            ["Alice", "Bob", "Charlie", "O'Brien, Dave"]
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use gpui::Global;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::appconfig;
+use crate::filter::FilterOptions;
+use crate::viewstate::{SortKey, ViewState};
+
+const FILTER_PRESETS_NAME: &str = "presets.toml";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PresetFilter {
+    pub column: String,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub options: FilterOptions,
+}
+
+/// A named set of column filters and sort keys for the layers of matching files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilterPreset {
+    pub name: String,
+    /// The path of the file, or a pattern with `*` and `?` wildcards
+    /// A pattern without a path separator is matched against the file name
+    pub file: String,
+    /// The layer the preset applies to, all layers when not set
+    #[serde(default)]
+    pub layer: Option<String>,
+    #[serde(default)]
+    pub filters: Vec<PresetFilter>,
+    #[serde(default)]
+    pub sort: Vec<SortKey>,
+}
+
+impl FilterPreset {
+    /// Create a preset from the filters and sort keys of the view state
+    pub fn new(name: &str, file: &str, layer: Option<&str>, view_state: &ViewState) -> Self {
+        Self {
+            name: name.to_string(),
+            file: file.to_string(),
+            layer: layer.map(str::to_string),
+            filters: view_state
+                .filters
+                .iter()
+                .map(|filter| PresetFilter {
+                    column: filter.column.clone(),
+                    text: filter.text().to_string(),
+                    options: filter.options(),
+                })
+                .collect(),
+            sort: view_state.sort.clone(),
+        }
+    }
+
+    /// The path should be canonical, it is compared as is with the file of the preset
+    pub fn matches(&self, path: &Path, layer: &str) -> bool {
+        if self
+            .layer
+            .as_deref()
+            .is_some_and(|preset_layer| preset_layer != layer)
+        {
+            return false;
+        }
+
+        if !self.file.contains(['*', '?']) {
+            return path == Path::new(&self.file);
+        }
+
+        if self.file.contains(['/', '\\']) {
+            wildcard_match(&self.file, &path.to_string_lossy())
+        } else {
+            path.file_name()
+                .is_some_and(|name| wildcard_match(&self.file, &name.to_string_lossy()))
+        }
+    }
+}
+
+/// Match the text against a pattern where `*` matches any sequence of characters and `?` a single character
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut pattern_ix, mut text_ix) = (0, 0);
+    // The position of the last `*` and the text position it currently matches up to
+    let mut backtrack: Option<(usize, usize)> = None;
+    while text_ix < text.len() {
+        match pattern.get(pattern_ix) {
+            Some('*') => {
+                backtrack = Some((pattern_ix, text_ix));
+                pattern_ix += 1;
+            }
+            Some(&c) if c == '?' || c == text[text_ix] => {
+                pattern_ix += 1;
+                text_ix += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` match one more character
+                Some((star_ix, star_text_ix)) => {
+                    backtrack = Some((star_ix, star_text_ix + 1));
+                    pattern_ix = star_ix + 1;
+                    text_ix = star_text_ix + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[pattern_ix..].iter().all(|&c| c == '*')
+}
+
+/// The saved filter presets
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FilterPresets {
+    #[serde(default, rename = "preset")]
+    presets: Vec<FilterPreset>,
+    #[serde(skip)]
+    storage_path: Option<PathBuf>,
+}
+
+impl Global for FilterPresets {}
+
+impl FilterPresets {
+    /// Load the presets that are stored next to the default config file
+    pub fn load() -> Self {
+        let (mut presets, storage_path) =
+            appconfig::load_stored::<Self>(FILTER_PRESETS_NAME, "filter presets");
+        presets.storage_path = storage_path;
+        presets
+    }
+
+    pub fn save(&self) {
+        if let Some(path) = &self.storage_path
+            && let Err(err) = appconfig::save_toml(self, path, "filter presets")
+        {
+            log::warn!("{err:#}");
+        }
+    }
+
+    pub fn presets(&self) -> &[FilterPreset] {
+        &self.presets
+    }
+
+    /// The indices of the presets that apply to the layer of the file, the path should be canonical
+    pub fn matching(&self, path: &Path, layer: &str) -> Vec<usize> {
+        self.presets
+            .iter()
+            .enumerate()
+            .filter(|(_, preset)| preset.matches(path, layer))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Add the preset, an existing preset with the same name, file and layer is replaced
+    pub fn add(&mut self, preset: FilterPreset) {
+        self.presets.retain(|existing| {
+            existing.name != preset.name
+                || existing.file != preset.file
+                || existing.layer != preset.layer
+        });
+        self.presets.push(preset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::temp_path;
+
+    fn preset(name: &str, file: &str, layer: Option<&str>) -> FilterPreset {
+        FilterPreset {
+            name: name.to_string(),
+            file: file.to_string(),
+            layer: layer.map(str::to_string),
+            filters: vec![PresetFilter {
+                column: "status".to_string(),
+                text: "open".to_string(),
+                options: FilterOptions::default(),
+            }],
+            sort: vec![SortKey::new("date", true)],
+        }
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("export_*.csv", "export_2024-01-31.csv"));
+        assert!(wildcard_match("export_????.csv", "export_0131.csv"));
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("*a*b", "xaxxab"));
+        assert!(!wildcard_match("export_*.csv", "export_2024-01-31.xlsx"));
+        assert!(!wildcard_match("export_?.csv", "export_12.csv"));
+    }
+
+    #[test]
+    fn test_matching_presets() {
+        let mut presets = FilterPresets::default();
+        presets.add(preset("daily", "export_*.csv", None));
+        presets.add(preset("sheet", "/nonexistent/data.xlsx", Some("Sheet2")));
+        presets.add(preset("other", "/nonexistent/*/data.xlsx", None));
+
+        let path = Path::new("/nonexistent/export_20240131.csv");
+        assert_eq!(presets.matching(path, "export_20240131"), [0]);
+
+        let path = Path::new("/nonexistent/data.xlsx");
+        assert_eq!(presets.matching(path, "Sheet2"), [1]);
+        assert!(presets.matching(path, "Sheet1").is_empty());
+
+        let path = Path::new("/nonexistent/2024/data.xlsx");
+        assert_eq!(presets.matching(path, "Sheet1"), [2]);
+    }
+
+    #[test]
+    fn test_add_replaces_existing_preset() {
+        let mut presets = FilterPresets::default();
+        presets.add(preset("daily", "export_*.csv", None));
+        presets.add(preset("daily", "export_*.csv", Some("a")));
+
+        let mut replacement = preset("daily", "export_*.csv", None);
+        replacement.sort.clear();
+        presets.add(replacement.clone());
+
+        assert_eq!(presets.presets().len(), 2);
+        assert_eq!(presets.presets()[1], replacement);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let path = temp_path("presets.toml");
+
+        let mut presets = FilterPresets::default();
+        presets.add(preset("daily", "export_*.csv", Some("a")));
+        appconfig::save_toml(&presets, &path, "filter presets").unwrap();
+
+        let loaded: FilterPresets = appconfig::load_toml(&path, "filter presets").unwrap();
+        assert_eq!(loaded.presets(), presets.presets());
+
+        std::fs::remove_file(path).ok();
+    }
+}
@@ -0,0 +1,250 @@
+use polars::prelude::{DataType, Expr, NamedFrom, PlSmallStr, Scalar, Series, lit};
+
+use crate::filter::FilterOptions;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+/// The parsed text of a column filter input
+///
+/// Supported syntax:
+/// - `>`, `>=`, `<`, `<=`, `=`, `!=` followed by a value: compare with the value
+/// - `a..b`: values in the inclusive range, `a..` and `..b` are open ranges
+/// - `null`: missing values
+/// - `!` followed by a filter: negate the filter (e.g. `!null`)
+/// - anything else: match the value as text
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterQuery {
+    Text(String),
+    Compare(Comparison, String),
+    Range(String, String),
+    IsNull,
+    Not(Box<FilterQuery>),
+}
+
+const OPERATORS: &[(&str, Comparison)] = &[
+    (">=", Comparison::GreaterOrEqual),
+    ("<=", Comparison::LessOrEqual),
+    ("!=", Comparison::NotEqual),
+    (">", Comparison::Greater),
+    ("<", Comparison::Less),
+    ("=", Comparison::Equal),
+];
+
+impl FilterQuery {
+    pub fn parse(text: &str) -> Self {
+        let trimmed = text.trim();
+        if trimmed.eq_ignore_ascii_case("null") {
+            return FilterQuery::IsNull;
+        }
+
+        for (operator, comparison) in OPERATORS {
+            if let Some(value) = trimmed.strip_prefix(operator) {
+                return FilterQuery::Compare(*comparison, value.trim().to_string());
+            }
+        }
+
+        if let Some(negated) = trimmed.strip_prefix('!') {
+            return FilterQuery::Not(Box::new(Self::parse(negated)));
+        }
+
+        if let Some((lower, upper)) = trimmed.split_once("..") {
+            let (lower, upper) = (lower.trim(), upper.trim());
+            match (lower.is_empty(), upper.is_empty()) {
+                (false, false) => {
+                    return FilterQuery::Range(lower.to_string(), upper.to_string());
+                }
+                (false, true) => {
+                    return FilterQuery::Compare(Comparison::GreaterOrEqual, lower.to_string());
+                }
+                (true, false) => {
+                    return FilterQuery::Compare(Comparison::LessOrEqual, upper.to_string());
+                }
+                (true, true) => {}
+            }
+        }
+
+        FilterQuery::Text(text.to_string())
+    }
+
+    /// Create the filter expression for a column with the given data type
+    /// Comparisons use the native column type, an error describes why the query does not apply to the column
+    pub fn compile(
+        &self,
+        value: Expr,
+        dtype: &DataType,
+        options: FilterOptions,
+    ) -> Result<Expr, String> {
+        match self {
+            FilterQuery::Text(text) if text.is_empty() => Err("Missing value".to_string()),
+            FilterQuery::Text(text) => Ok(text_match(value, text, options)),
+            FilterQuery::Compare(comparison, text) => {
+                let (value, literal) = comparison_operands(value, dtype, text, options)?;
+                Ok(match comparison {
+                    Comparison::Equal => value.eq(literal),
+                    Comparison::NotEqual => value.neq(literal),
+                    Comparison::Greater => value.gt(literal),
+                    Comparison::GreaterOrEqual => value.gt_eq(literal),
+                    Comparison::Less => value.lt(literal),
+                    Comparison::LessOrEqual => value.lt_eq(literal),
+                })
+            }
+            FilterQuery::Range(lower, upper) => {
+                let (value, lower) = comparison_operands(value, dtype, lower, options)?;
+                let (_, upper) = comparison_operands(value.clone(), dtype, upper, options)?;
+                Ok(value.clone().gt_eq(lower).and(value.lt_eq(upper)))
+            }
+            FilterQuery::IsNull => Ok(value.is_null()),
+            FilterQuery::Not(query) => Ok(query.compile(value, dtype, options)?.not()),
+        }
+    }
+}
+
+/// Match the text representation of the value
+fn text_match(value: Expr, text: &str, options: FilterOptions) -> Expr {
+    let value = value.cast(DataType::String);
+    let (value, text) = if options.case_sensitive {
+        (value, text.to_string())
+    } else {
+        (value.str().to_lowercase(), text.to_lowercase())
+    };
+
+    if options.whole_value {
+        value.eq(lit(text))
+    } else {
+        value.str().contains_literal(lit(text))
+    }
+}
+
+fn comparison_operands(
+    value: Expr,
+    dtype: &DataType,
+    text: &str,
+    options: FilterOptions,
+) -> Result<(Expr, Expr), String> {
+    if text.is_empty() {
+        return Err("Missing value".to_string());
+    }
+
+    if dtype.is_string() && !options.case_sensitive {
+        return Ok((value.str().to_lowercase(), lit(text.to_lowercase())));
+    }
+
+    Ok((value, typed_literal(text, dtype)?))
+}
+
+/// Convert the text to a literal of the column type
+fn typed_literal(text: &str, dtype: &DataType) -> Result<Expr, String> {
+    if dtype.is_numeric() {
+        return text
+            .parse::<f64>()
+            .map(lit)
+            .map_err(|_| format!("'{text}' is not a number"));
+    }
+
+    if dtype.is_bool() {
+        return match text.to_lowercase().as_str() {
+            "true" => Ok(lit(true)),
+            "false" => Ok(lit(false)),
+            _ => Err(format!("'{text}' is not a boolean (true/false)")),
+        };
+    }
+
+    if dtype.is_string() {
+        return Ok(lit(text.to_string()));
+    }
+
+    if dtype.is_temporal() {
+        let value = Series::new(PlSmallStr::EMPTY, [text])
+            .strict_cast(dtype)
+            .ok()
+            .and_then(|series| series.get(0).ok().map(|value| value.into_static()))
+            .filter(|value| !value.is_null())
+            .ok_or_else(|| format!("'{text}' is not a valid {dtype} value"))?;
+
+        return Ok(lit(Scalar::new(dtype.clone(), value)));
+    }
+
+    Err(format!("Comparisons are not supported on {dtype} columns"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            FilterQuery::parse("> 100"),
+            FilterQuery::Compare(Comparison::Greater, "100".to_string())
+        );
+        assert_eq!(
+            FilterQuery::parse(">=1.5"),
+            FilterQuery::Compare(Comparison::GreaterOrEqual, "1.5".to_string())
+        );
+        assert_eq!(
+            FilterQuery::parse("!= abc"),
+            FilterQuery::Compare(Comparison::NotEqual, "abc".to_string())
+        );
+        assert_eq!(
+            FilterQuery::parse("1.5..3"),
+            FilterQuery::Range("1.5".to_string(), "3".to_string())
+        );
+        assert_eq!(
+            FilterQuery::parse("2024-01-01.."),
+            FilterQuery::Compare(Comparison::GreaterOrEqual, "2024-01-01".to_string())
+        );
+        assert_eq!(FilterQuery::parse("NULL"), FilterQuery::IsNull);
+        assert_eq!(
+            FilterQuery::parse("!null"),
+            FilterQuery::Not(Box::new(FilterQuery::IsNull))
+        );
+        assert_eq!(
+            FilterQuery::parse("!abc"),
+            FilterQuery::Not(Box::new(FilterQuery::Text("abc".to_string())))
+        );
+        assert_eq!(
+            FilterQuery::parse("abc"),
+            FilterQuery::Text("abc".to_string())
+        );
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        let options = FilterOptions::default();
+        let value = || polars::prelude::col("value");
+
+        assert!(
+            FilterQuery::parse("> abc")
+                .compile(value(), &DataType::Int32, options)
+                .is_err()
+        );
+        assert!(
+            FilterQuery::parse(">")
+                .compile(value(), &DataType::Int32, options)
+                .is_err()
+        );
+        assert!(
+            FilterQuery::parse("> maybe")
+                .compile(value(), &DataType::Boolean, options)
+                .is_err()
+        );
+        assert!(
+            FilterQuery::parse("> 2024-13-45")
+                .compile(value(), &DataType::Date, options)
+                .is_err()
+        );
+        assert!(
+            FilterQuery::parse("> 2024-01-31")
+                .compile(value(), &DataType::Date, options)
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use gpui::{EntityId, Global};
+use std::path::{Path, PathBuf};
+
+use crate::tablelayer::LayerSnapshot;
+
+struct CacheEntry {
+    document: EntityId,
+    path: PathBuf,
+    layer: String,
+    size: usize,
+    snapshot: LayerSnapshot,
+}
+
+/// In-memory cache of the loaded layers and their filter and sort state
+/// Every document tab has its own entries, so documents of the same file keep their own state
+/// Least recently used layers are evicted when the memory budget is exceeded
+pub struct LayerCache {
+    budget: usize,
+    /// Most recently used entry first
+    entries: Vec<CacheEntry>,
+}
+
+impl Global for LayerCache {}
+
+impl LayerCache {
+    /// Create a cache that holds at most `budget` bytes of (estimated) layer data
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn get(&mut self, document: EntityId, path: &Path, layer: &str) -> Option<LayerSnapshot> {
+        let index = self.position(document, path, layer)?;
+        let entry = self.entries.remove(index);
+        let snapshot = entry.snapshot.clone();
+        self.entries.insert(0, entry);
+        Some(snapshot)
+    }
+
+    pub fn insert(
+        &mut self,
+        document: EntityId,
+        path: &Path,
+        layer: &str,
+        snapshot: LayerSnapshot,
+    ) {
+        if let Some(index) = self.position(document, path, layer) {
+            self.entries.remove(index);
+        }
+
+        self.entries.insert(
+            0,
+            CacheEntry {
+                document,
+                path: path.to_path_buf(),
+                layer: layer.to_string(),
+                size: snapshot.estimated_size(),
+                snapshot,
+            },
+        );
+
+        self.evict();
+    }
+
+    /// Drop the cached layers of a file in a document, e.g. when the file is reloaded from disk
+    pub fn remove_file(&mut self, document: EntityId, path: &Path) {
+        self.entries
+            .retain(|entry| entry.document != document || entry.path != path);
+    }
+
+    fn position(&self, document: EntityId, path: &Path, layer: &str) -> Option<usize> {
+        self.entries.iter().position(|entry| {
+            entry.document == document && entry.path == path && entry.layer == layer
+        })
+    }
+
+    fn used(&self) -> usize {
+        self.entries.iter().map(|entry| entry.size).sum()
+    }
+
+    /// The most recently used entry is always kept, even if it exceeds the budget on its own
+    fn evict(&mut self) {
+        while self.entries.len() > 1 && self.used() > self.budget {
+            if let Some(entry) = self.entries.pop() {
+                log::debug!(
+                    "Evict layer {} of {} from the cache",
+                    entry.layer,
+                    entry.path.display()
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+
+    fn document() -> EntityId {
+        EntityId::from(1)
+    }
+
+    fn snapshot(rows: i32) -> LayerSnapshot {
+        let data = df!("values" => (0..rows).collect::<Vec<i32>>()).unwrap();
+        LayerSnapshot::new(data)
+    }
+
+    #[test]
+    fn test_least_recently_used_layer_is_evicted() {
+        let layer_size = snapshot(1000).estimated_size();
+        let mut cache = LayerCache::new(layer_size * 2);
+        let path = Path::new("/nonexistent/data.xlsx");
+
+        cache.insert(document(), path, "a", snapshot(1000));
+        cache.insert(document(), path, "b", snapshot(1000));
+        // Use "a" so "b" becomes the least recently used layer
+        assert!(cache.get(document(), path, "a").is_some());
+        cache.insert(document(), path, "c", snapshot(1000));
+
+        assert!(cache.get(document(), path, "a").is_some());
+        assert!(cache.get(document(), path, "b").is_none());
+        assert!(cache.get(document(), path, "c").is_some());
+    }
+
+    #[test]
+    fn test_layer_exceeding_budget_is_kept() {
+        let mut cache = LayerCache::new(1);
+        let path = Path::new("/nonexistent/data.xlsx");
+
+        cache.insert(document(), path, "a", snapshot(1000));
+        cache.insert(document(), path, "b", snapshot(1000));
+
+        assert!(cache.get(document(), path, "a").is_none());
+        assert!(cache.get(document(), path, "b").is_some());
+    }
+
+    #[test]
+    fn test_remove_file() {
+        let mut cache = LayerCache::new(usize::MAX);
+        let path = Path::new("/nonexistent/data.xlsx");
+        let other_path = Path::new("/nonexistent/other.xlsx");
+
+        cache.insert(document(), path, "a", snapshot(10));
+        cache.insert(document(), other_path, "a", snapshot(10));
+        cache.remove_file(document(), path);
+
+        assert!(cache.get(document(), path, "a").is_none());
+        assert!(cache.get(document(), other_path, "a").is_some());
+    }
+
+    #[test]
+    fn test_documents_have_their_own_layers() {
+        let mut cache = LayerCache::new(usize::MAX);
+        let path = Path::new("/nonexistent/data.xlsx");
+        let other_document = EntityId::from(2);
+
+        cache.insert(document(), path, "a", snapshot(10));
+        cache.insert(other_document, path, "a", snapshot(20));
+        cache.remove_file(document(), path);
+
+        assert!(cache.get(document(), path, "a").is_none());
+        assert_eq!(
+            cache
+                .get(other_document, path, "a")
+                .map(|snapshot| snapshot.original_data().height()),
+            Some(20)
+        );
+    }
+}
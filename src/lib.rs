@@ -1,26 +1,113 @@
 use gpui::{App, KeyBinding};
 
-use crate::tabulite::Open;
+use crate::appconfig::AppConfig;
+use crate::csvsettings::CsvSettings;
+use crate::filterpresets::FilterPresets;
+use crate::layercache::LayerCache;
+use crate::queryhistory::QueryHistory;
+use crate::recentfiles::RecentFiles;
+use crate::tableio::ReadOptions;
+use crate::tabulite::CloseTab;
+use crate::tabulite::CopySelection;
+use crate::tabulite::ExportAs;
+use crate::tabulite::NewTab;
+use crate::tabulite::OpenRecent;
+use crate::tabulite::ProfileDataset;
 use crate::tabulite::Quit;
+use crate::tabulite::SelectColumns;
+use crate::tabulite::SelectRows;
 use crate::tabulite::ToggleFilter;
+use crate::tabulite::ToggleQueryPanel;
+use crate::tabulite::ToggleSearch;
+use crate::tabulite::{Open, OpenDirectory};
 
 pub mod appconfig;
-mod tableio;
+mod columnstats;
+mod csvdialect;
+mod csvdialog;
+mod csvsettings;
+mod filter;
+mod filterpresets;
+mod filterquery;
+mod layercache;
+mod nested;
+pub mod profile;
+mod queryhistory;
+mod recentfiles;
+mod search;
+mod selection;
+mod sqlite;
+mod sqlquery;
+pub mod tableio;
 mod tablelayer;
 mod tableview;
+mod tablewriter;
 pub mod tabulite;
+#[cfg(test)]
+mod testdata;
+pub mod textencoding;
 mod utils;
+mod valuefilter;
+mod viewstate;
 
-pub fn init(cx: &mut App) {
+const RECENT_FILES_CONTEXT: &str = "RecentFiles";
+/// The table has focus, the filter inputs in its header excluded
+const TABLE_CONTEXT: &str = "Table && !Input";
+
+pub fn init(config: &AppConfig, cx: &mut App) {
     gpui_component::init(cx);
+    cx.set_global(RecentFiles::load());
+    cx.set_global(QueryHistory::load());
+    cx.set_global(FilterPresets::load());
+    cx.set_global(LayerCache::new(config.layer_cache_size * 1024 * 1024));
+    cx.set_global(CsvSettings::load());
+    cx.set_global(ReadOptions {
+        row_limit: (config.database_row_limit > 0).then_some(config.database_row_limit),
+        encoding: config.encoding,
+        ..Default::default()
+    });
 
     cx.bind_keys([
         KeyBinding::new("/", ToggleFilter, None),
         #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-f", ToggleSearch, None),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-f", ToggleSearch, None),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-e", ToggleQueryPanel, None),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-e", ToggleQueryPanel, None),
+        #[cfg(target_os = "macos")]
         KeyBinding::new("cmd-o", Open, None),
         #[cfg(not(target_os = "macos"))]
         KeyBinding::new("ctrl-o", Open, None),
         #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-o", OpenDirectory, None),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-o", OpenDirectory, None),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-s", ExportAs { index: 0 }, None),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-s", ExportAs { index: 0 }, None),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-p", ProfileDataset, None),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-p", ProfileDataset, None),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-t", NewTab, None),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-t", NewTab, None),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-w", CloseTab, None),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-w", CloseTab, None),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-c", CopySelection, Some(TABLE_CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-c", CopySelection, Some(TABLE_CONTEXT)),
+        KeyBinding::new("shift-space", SelectRows, Some(TABLE_CONTEXT)),
+        KeyBinding::new("ctrl-space", SelectColumns, Some(TABLE_CONTEXT)),
+        #[cfg(target_os = "macos")]
         KeyBinding::new("cmd-q", Quit, None),
         #[cfg(target_os = "windows")]
         KeyBinding::new("alt-f4", Quit, None),
@@ -28,6 +115,15 @@ pub fn init(cx: &mut App) {
         KeyBinding::new("ctrl-q", Quit, None),
     ]);
 
+    // Number keys reopen the recent files listed on the empty screen
+    cx.bind_keys((0..9).map(|index| {
+        KeyBinding::new(
+            &(index + 1).to_string(),
+            OpenRecent { index },
+            Some(RECENT_FILES_CONTEXT),
+        )
+    }));
+
     cx.on_action(|_: &Quit, cx: &mut App| {
         cx.quit();
     });
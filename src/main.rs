@@ -2,28 +2,50 @@ use gpui::*;
 use gpui_component::*;
 use gpui_component_assets::Assets;
 use std::path::PathBuf;
-use tabulite::{appconfig, tabulite::Tabulite};
+use tabulite::{
+    appconfig, profile, tableio::ReadOptions, tabulite::Tabulite, textencoding::TextEncoding,
+};
 
 fn main() {
     env_logger::init();
 
-    let app = Application::new().with_assets(Assets);
-
     use clap::Parser;
 
     #[derive(Parser, Debug)]
     #[command(author, version, about, long_about = None)]
     struct Args {
-        input_file: Option<std::path::PathBuf>,
+        /// The files to open, each file is opened in its own tab
+        input_files: Vec<std::path::PathBuf>,
         #[arg(short = 'c', long = "config")]
         config_file: Option<std::path::PathBuf>,
+        /// Write a profiling report (.html or .md) of the input files and exit without opening a window
+        #[arg(long = "profile", value_name = "REPORT", requires = "input_files")]
+        profile_report: Option<std::path::PathBuf>,
+        /// The character encoding of csv files (e.g. utf-8, windows-1252, latin1, utf-16le), detected when not set
+        #[arg(short = 'e', long = "encoding")]
+        encoding: Option<TextEncoding>,
     }
 
     let args = Args::parse();
-    let config = appconfig::load_config(args.config_file.as_deref());
+    if let Some(report) = &args.profile_report {
+        let options = ReadOptions {
+            encoding: args.encoding,
+            ..Default::default()
+        };
+        if let Err(err) = profile::write_report(&args.input_files, report, &options) {
+            eprintln!("{err:#}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let mut config = appconfig::load_config(args.config_file.as_deref());
+    // The command line option overrides the configured encoding
+    config.encoding = args.encoding.or(config.encoding);
+    let app = Application::new().with_assets(Assets);
 
     app.run(move |cx| {
-        tabulite::init(cx);
+        tabulite::init(&config, cx);
 
         let theme_name = SharedString::from(config.theme);
         let themes_dir = std::env::var("CARGO_MANIFEST_DIR")
@@ -39,7 +61,7 @@ fn main() {
 
         cx.spawn(async move |cx| {
             cx.open_window(WindowOptions::default(), |window, cx| {
-                let app = Tabulite::view(args.input_file, window, cx);
+                let app = Tabulite::view(args.input_files, window, cx);
                 cx.new(|cx| Root::new(app, window, cx))
             })?;
 
@@ -0,0 +1,123 @@
+use polars::frame::DataFrame;
+use polars::prelude::{
+    AnyValue, DataType, IntoColumn as _, PlSmallStr, PolarsResult, Series, polars_err,
+};
+
+/// Replace the struct column by its fields, the field columns are named `column.field`
+pub fn expand_struct(data: &DataFrame, column: &str) -> PolarsResult<DataFrame> {
+    let position = data
+        .get_column_index(column)
+        .ok_or_else(|| polars_err!(ColumnNotFound: "{column}"))?;
+    let fields = data
+        .column(column)?
+        .as_materialized_series()
+        .struct_()?
+        .fields_as_series();
+
+    let mut columns = data.get_columns().to_vec();
+    columns.splice(
+        position..=position,
+        fields.into_iter().map(|field| {
+            let name = format!("{column}.{}", field.name());
+            field.with_name(name.into()).into_column()
+        }),
+    );
+
+    DataFrame::new(columns)
+}
+
+/// The items of a list value as a table, a list of structs gets a column per field
+pub fn list_items(items: Series) -> PolarsResult<DataFrame> {
+    match items.dtype() {
+        DataType::Struct(_) => Ok(items.struct_()?.clone().unnest()),
+        _ => DataFrame::new(vec![items.into_column()]),
+    }
+}
+
+/// A short description of a nested value, `None` for values that are not nested
+pub fn nested_label(value: &AnyValue) -> Option<String> {
+    match value {
+        AnyValue::List(items) if items.len() == 1 => Some("[1 item]".to_string()),
+        AnyValue::List(items) => Some(format!("[{} items]", items.len())),
+        AnyValue::Struct(_, _, fields) => Some(struct_label(fields.iter().map(|f| f.name()))),
+        AnyValue::StructOwned(value) => Some(struct_label(value.1.iter().map(|f| f.name()))),
+        _ => None,
+    }
+}
+
+fn struct_label<'a>(names: impl Iterator<Item = &'a PlSmallStr>) -> String {
+    format!(
+        "{{{}}}",
+        names
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::people;
+    use polars::prelude::*;
+
+    /// The people with their city and age combined in a struct column
+    fn test_data() -> DataFrame {
+        let data = people();
+        let fields =
+            ["city", "age"].map(|name| data.column(name).unwrap().as_materialized_series());
+        let details =
+            StructChunked::from_series("details".into(), data.height(), fields.into_iter())
+                .unwrap()
+                .into_series();
+
+        DataFrame::new(vec![
+            data.column("name").unwrap().clone(),
+            details.into_column(),
+            data.column("score").unwrap().clone(),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_expand_struct() {
+        let expanded = expand_struct(&test_data(), "details").unwrap();
+        let names: Vec<_> = expanded
+            .get_column_names()
+            .into_iter()
+            .map(|name| name.as_str())
+            .collect();
+        assert_eq!(names, ["name", "details.city", "details.age", "score"]);
+        assert_eq!(
+            expanded.column("details.age").unwrap().get(1).unwrap(),
+            AnyValue::Int32(42)
+        );
+
+        assert!(expand_struct(&test_data(), "name").is_err());
+        assert!(expand_struct(&test_data(), "missing").is_err());
+    }
+
+    #[test]
+    fn test_nested_label() {
+        let data = test_data();
+        let value = data.column("details").unwrap().get(0).unwrap();
+        assert_eq!(nested_label(&value).as_deref(), Some("{city, age}"));
+
+        let items = Series::new("tags".into(), ["a", "b"]);
+        assert_eq!(
+            nested_label(&AnyValue::List(items)).as_deref(),
+            Some("[2 items]")
+        );
+        assert_eq!(nested_label(&AnyValue::Int32(1)), None);
+    }
+
+    #[test]
+    fn test_list_items() {
+        let items = list_items(Series::new("tags".into(), ["a", "b"])).unwrap();
+        assert_eq!(items.shape(), (2, 1));
+
+        let details = test_data().column("details").unwrap().clone();
+        let items = list_items(details.as_materialized_series().clone()).unwrap();
+        assert_eq!(items.shape(), (5, 2));
+    }
+}
@@ -0,0 +1,365 @@
+use anyhow::{Context, Result, bail};
+use polars::frame::{DataFrame, UniqueKeepStrategy};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::columnstats::{self, ColumnStats};
+use crate::tableio::{self, ReadOptions};
+
+/// The width in characters of the largest histogram bar in a markdown report
+const MARKDOWN_BAR_WIDTH: usize = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Html,
+    Markdown,
+}
+
+impl ReportFormat {
+    /// Determine the report format from the extension of the report path
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            Some("html" | "htm") => Ok(Self::Html),
+            Some("md" | "markdown") => Ok(Self::Markdown),
+            _ => bail!(
+                "Unsupported report format, use an .html or .md file: {}",
+                path.display()
+            ),
+        }
+    }
+}
+
+struct LayerProfile {
+    name: String,
+    rows: usize,
+    /// `None` when the rows could not be compared, e.g. for nested columns
+    duplicate_rows: Option<usize>,
+    columns: Vec<ColumnStats>,
+}
+
+struct FileProfile {
+    path: PathBuf,
+    layers: Vec<LayerProfile>,
+}
+
+fn profile_layer(name: &str, data: &DataFrame) -> Result<LayerProfile> {
+    let duplicate_rows = data
+        .unique_stable(None, UniqueKeepStrategy::Any, None)
+        .map(|unique| data.height() - unique.height())
+        .ok();
+
+    let columns = data
+        .get_columns()
+        .iter()
+        .map(|column| columnstats::column_stats(column.as_materialized_series()))
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Failed to calculate the statistics of layer {name}"))?;
+
+    Ok(LayerProfile {
+        name: name.to_string(),
+        rows: data.height(),
+        duplicate_rows,
+        columns,
+    })
+}
+
+fn profile_file(path: &Path, options: &ReadOptions) -> Result<FileProfile> {
+    let layers = tableio::layers_for_path(path)
+        .with_context(|| format!("Failed to read the layers of {}", path.display()))?
+        .iter()
+        .map(|layer| {
+            let data = tableio::layer_data(path, layer, options)
+                .with_context(|| format!("Failed to read layer {layer} of {}", path.display()))?;
+            profile_layer(layer, &data)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(FileProfile {
+        path: path.to_path_buf(),
+        layers,
+    })
+}
+
+/// Profile every layer of the input files and write the report, the format follows from the report extension
+/// The layers are read with the provided options, e.g. the csv options that were chosen for a file
+pub fn write_report(inputs: &[PathBuf], report: &Path, options: &ReadOptions) -> Result<()> {
+    if inputs.is_empty() {
+        bail!("No input files to profile");
+    }
+
+    let format = ReportFormat::from_path(report)?;
+    let files = inputs
+        .iter()
+        .map(|path| profile_file(path, options))
+        .collect::<Result<Vec<_>>>()?;
+
+    let contents = match format {
+        ReportFormat::Html => render_html(&files),
+        ReportFormat::Markdown => render_markdown(&files),
+    };
+
+    fs::write(report, contents)
+        .with_context(|| format!("Failed to write report: {}", report.display()))
+}
+
+fn null_ratio(stats: &ColumnStats) -> f64 {
+    if stats.count == 0 {
+        0.0
+    } else {
+        stats.null_count as f64 / stats.count as f64
+    }
+}
+
+/// The min, max, mean, median and standard deviation, empty for non numeric columns
+fn numeric_cells(stats: &ColumnStats) -> [String; 5] {
+    match &stats.numeric {
+        Some(numeric) => [
+            columnstats::format_number(numeric.min),
+            columnstats::format_number(numeric.max),
+            columnstats::format_number(numeric.mean),
+            columnstats::format_number(numeric.median),
+            numeric
+                .std_dev
+                .map(columnstats::format_number)
+                .unwrap_or_default(),
+        ],
+        None => Default::default(),
+    }
+}
+
+fn format_duplicates(duplicate_rows: Option<usize>) -> String {
+    duplicate_rows.map_or_else(|| "n/a".to_string(), |count| count.to_string())
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\n', '\r'], " ")
+}
+
+fn render_markdown(files: &[FileProfile]) -> String {
+    let mut out = String::from("# Data profile\n");
+
+    for file in files {
+        let _ = writeln!(
+            out,
+            "\n## {}",
+            escape_markdown(&file.path.to_string_lossy())
+        );
+
+        for layer in &file.layers {
+            let _ = writeln!(out, "\n### {}\n", escape_markdown(&layer.name));
+            let _ = writeln!(
+                out,
+                "{} rows, {} columns, {} duplicate rows\n",
+                layer.rows,
+                layer.columns.len(),
+                format_duplicates(layer.duplicate_rows)
+            );
+
+            out.push_str("| Column | Type | Missing | Distinct | Min | Max | Mean | Median | Std. deviation |\n");
+            out.push_str("|---|---|---:|---:|---:|---:|---:|---:|---:|\n");
+            for stats in &layer.columns {
+                let [min, max, mean, median, std_dev] = numeric_cells(stats);
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} ({:.1}%) | {} | {min} | {max} | {mean} | {median} | {std_dev} |",
+                    escape_markdown(&stats.name),
+                    stats.dtype,
+                    stats.null_count,
+                    null_ratio(stats) * 100.0,
+                    stats.distinct_count,
+                );
+            }
+
+            for stats in &layer.columns {
+                if stats.histogram.is_empty() {
+                    continue;
+                }
+
+                let max_count = stats
+                    .histogram
+                    .iter()
+                    .map(|bin| bin.count)
+                    .max()
+                    .unwrap_or(1)
+                    .max(1);
+                let _ = writeln!(out, "\n#### {}\n", escape_markdown(&stats.name));
+                out.push_str("| Value | Count | |\n|---|---:|---|\n");
+                for bin in &stats.histogram {
+                    let _ = writeln!(
+                        out,
+                        "| {} | {} | {} |",
+                        escape_markdown(&bin.label),
+                        bin.count,
+                        "█".repeat(bin.count * MARKDOWN_BAR_WIDTH / max_count)
+                    );
+                }
+            }
+        }
+    }
+
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const HTML_STYLE: &str = "body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.5em; text-align: left; }
+td.number { text-align: right; }
+.bar { background: #4a7fd4; height: 0.8em; }
+.histograms { display: flex; flex-wrap: wrap; gap: 1em; }
+.histograms table { width: 22em; }";
+
+fn render_html(files: &[FileProfile]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Data profile</title>\n<style>\n{HTML_STYLE}\n</style>\n</head>\n<body>\n<h1>Data profile</h1>\n"
+    );
+
+    for file in files {
+        let _ = writeln!(
+            out,
+            "<h2>{}</h2>",
+            escape_html(&file.path.to_string_lossy())
+        );
+
+        for layer in &file.layers {
+            let _ = writeln!(out, "<h3>{}</h3>", escape_html(&layer.name));
+            let _ = writeln!(
+                out,
+                "<p>{} rows, {} columns, {} duplicate rows</p>",
+                layer.rows,
+                layer.columns.len(),
+                format_duplicates(layer.duplicate_rows)
+            );
+
+            out.push_str("<table>\n<tr><th>Column</th><th>Type</th><th>Missing</th><th>Distinct</th><th>Min</th><th>Max</th><th>Mean</th><th>Median</th><th>Std. deviation</th></tr>\n");
+            for stats in &layer.columns {
+                let [min, max, mean, median, std_dev] = numeric_cells(stats);
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td>{}</td><td class=\"number\">{} ({:.1}%)</td><td class=\"number\">{}</td><td class=\"number\">{min}</td><td class=\"number\">{max}</td><td class=\"number\">{mean}</td><td class=\"number\">{median}</td><td class=\"number\">{std_dev}</td></tr>",
+                    escape_html(&stats.name),
+                    escape_html(&stats.dtype.to_string()),
+                    stats.null_count,
+                    null_ratio(stats) * 100.0,
+                    stats.distinct_count,
+                );
+            }
+            out.push_str("</table>\n<div class=\"histograms\">\n");
+
+            for stats in &layer.columns {
+                if stats.histogram.is_empty() {
+                    continue;
+                }
+
+                let max_count = stats
+                    .histogram
+                    .iter()
+                    .map(|bin| bin.count)
+                    .max()
+                    .unwrap_or(1)
+                    .max(1);
+                let _ = writeln!(
+                    out,
+                    "<table>\n<tr><th colspan=\"3\">{}</th></tr>",
+                    escape_html(&stats.name)
+                );
+                for bin in &stats.histogram {
+                    let _ = writeln!(
+                        out,
+                        "<tr><td>{}</td><td class=\"number\">{}</td><td><div class=\"bar\" style=\"width: {:.1}%\"></div></td></tr>",
+                        escape_html(&bin.label),
+                        bin.count,
+                        bin.count as f64 * 100.0 / max_count as f64
+                    );
+                }
+                out.push_str("</table>\n");
+            }
+            out.push_str("</div>\n");
+        }
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::temp_path;
+    use polars::prelude::*;
+
+    fn test_profile() -> Vec<FileProfile> {
+        let data = df!(
+            "name" => [Some("Alice"), Some("Bob"), Some("Alice"), None],
+            "age" => [Some(31), Some(42), Some(31), Some(8)],
+            "city" => ["Gent", "<Brussel>", "Gent", "Antwerpen"],
+        )
+        .unwrap();
+
+        vec![FileProfile {
+            path: PathBuf::from("/data/people.csv"),
+            layers: vec![profile_layer("people", &data).unwrap()],
+        }]
+    }
+
+    #[test]
+    fn test_report_format_from_path() {
+        assert_eq!(
+            ReportFormat::from_path(Path::new("report.HTML")).unwrap(),
+            ReportFormat::Html
+        );
+        assert_eq!(
+            ReportFormat::from_path(Path::new("report.md")).unwrap(),
+            ReportFormat::Markdown
+        );
+        assert!(ReportFormat::from_path(Path::new("report.pdf")).is_err());
+    }
+
+    #[test]
+    fn test_write_report_without_inputs() {
+        let report = temp_path("empty_profile.md");
+        assert!(write_report(&[], &report, &ReadOptions::default()).is_err());
+        assert!(!report.exists());
+    }
+
+    #[test]
+    fn test_profile_layer() {
+        let layer = &test_profile()[0].layers[0];
+        assert_eq!(layer.rows, 4);
+        assert_eq!(layer.duplicate_rows, Some(1));
+        assert_eq!(layer.columns.len(), 3);
+        assert_eq!(layer.columns[0].null_count, 1);
+        assert!(layer.columns[1].numeric.is_some());
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let report = render_markdown(&test_profile());
+        assert!(report.contains("### people"));
+        assert!(report.contains("4 rows, 3 columns, 1 duplicate rows"));
+        assert!(report.contains("| name | str | 1 (25.0%) | 2 |"));
+        assert!(report.contains("| age | i32 | 0 (0.0%) | 3 | 8 | 42 | 28 |"));
+        assert!(report.contains("#### city"));
+    }
+
+    #[test]
+    fn test_render_html() {
+        let report = render_html(&test_profile());
+        assert!(report.starts_with("<!DOCTYPE html>"));
+        assert!(report.contains("<h3>people</h3>"));
+        assert!(report.contains("&lt;Brussel&gt;"));
+        assert!(!report.contains("<Brussel>"));
+    }
+}
@@ -0,0 +1,84 @@
+use gpui::Global;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::appconfig;
+
+const QUERY_HISTORY_NAME: &str = "query_history.toml";
+const MAX_QUERIES: usize = 50;
+
+/// Previously executed SQL queries, the most recent one first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryHistory {
+    #[serde(default)]
+    queries: Vec<String>,
+    #[serde(skip)]
+    storage_path: Option<PathBuf>,
+}
+
+impl Global for QueryHistory {}
+
+impl QueryHistory {
+    /// Load the query history that is stored next to the default config file
+    pub fn load() -> Self {
+        let (mut history, storage_path) =
+            appconfig::load_stored::<Self>(QUERY_HISTORY_NAME, "query history");
+        history.storage_path = storage_path;
+        history
+    }
+
+    pub fn save(&self) {
+        if let Some(path) = &self.storage_path
+            && let Err(err) = appconfig::save_toml(self, path, "query history")
+        {
+            log::warn!("{err:#}");
+        }
+    }
+
+    pub fn queries(&self) -> &[String] {
+        &self.queries
+    }
+
+    /// Move the query to the front of the history
+    pub fn add(&mut self, query: &str) {
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+
+        self.queries.retain(|existing| existing != query);
+        self.queries.insert(0, query.to_string());
+        self.queries.truncate(MAX_QUERIES);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::temp_path;
+
+    #[test]
+    fn test_add_moves_existing_query_to_front() {
+        let mut history = QueryHistory::default();
+        history.add("SELECT * FROM a");
+        history.add("SELECT * FROM b");
+        history.add("  SELECT * FROM a\n");
+        history.add("");
+
+        assert_eq!(history.queries(), ["SELECT * FROM a", "SELECT * FROM b"]);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let path = temp_path("query_history.toml");
+
+        let mut history = QueryHistory::default();
+        history.add("SELECT *\nFROM a");
+        appconfig::save_toml(&history, &path, "query history").unwrap();
+
+        let loaded: QueryHistory = appconfig::load_toml(&path, "query history").unwrap();
+        assert_eq!(loaded.queries(), history.queries());
+
+        std::fs::remove_file(path).ok();
+    }
+}
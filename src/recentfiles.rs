@@ -0,0 +1,118 @@
+use gpui::Global;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::appconfig;
+
+const RECENT_FILES_NAME: &str = "recent.toml";
+const MAX_RECENT_FILES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentFile {
+    pub path: PathBuf,
+    /// The layer that was active when the file was last viewed
+    #[serde(default)]
+    pub layer: Option<String>,
+}
+
+/// Most recently used files, the most recent one first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecentFiles {
+    #[serde(default)]
+    files: Vec<RecentFile>,
+    #[serde(skip)]
+    storage_path: Option<PathBuf>,
+}
+
+impl Global for RecentFiles {}
+
+impl RecentFiles {
+    /// Load the recent files list that is stored next to the default config file
+    pub fn load() -> Self {
+        let (mut recent, storage_path) =
+            appconfig::load_stored::<Self>(RECENT_FILES_NAME, "recent files");
+        recent.storage_path = storage_path;
+        recent
+    }
+
+    pub fn save(&self) {
+        if let Some(path) = &self.storage_path
+            && let Err(err) = appconfig::save_toml(self, path, "recent files")
+        {
+            log::warn!("{err:#}");
+        }
+    }
+
+    pub fn files(&self) -> &[RecentFile] {
+        &self.files
+    }
+
+    /// Move the path to the front of the list, the stored layer is kept when no layer is provided
+    pub fn add(&mut self, path: &Path, layer: Option<&str>) {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+        let mut entry = match self.files.iter().position(|file| file.path == path) {
+            Some(index) => self.files.remove(index),
+            None => RecentFile { path, layer: None },
+        };
+
+        if let Some(layer) = layer {
+            entry.layer = Some(layer.to_string());
+        }
+
+        self.files.insert(0, entry);
+        self.files.truncate(MAX_RECENT_FILES);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::temp_path;
+
+    #[test]
+    fn test_add_moves_existing_entry_to_front() {
+        let mut recent = RecentFiles::default();
+        recent.add(Path::new("/nonexistent/a.csv"), None);
+        recent.add(Path::new("/nonexistent/b.xlsx"), Some("Sheet2"));
+        recent.add(Path::new("/nonexistent/a.csv"), Some("a"));
+
+        assert_eq!(recent.files().len(), 2);
+        assert_eq!(recent.files()[0].path, Path::new("/nonexistent/a.csv"));
+        assert_eq!(recent.files()[0].layer.as_deref(), Some("a"));
+        assert_eq!(recent.files()[1].layer.as_deref(), Some("Sheet2"));
+
+        // Adding without a layer keeps the last active layer
+        recent.add(Path::new("/nonexistent/b.xlsx"), None);
+        assert_eq!(recent.files()[0].layer.as_deref(), Some("Sheet2"));
+    }
+
+    #[test]
+    fn test_list_is_limited() {
+        let mut recent = RecentFiles::default();
+        for i in 0..MAX_RECENT_FILES + 5 {
+            recent.add(Path::new(&format!("/nonexistent/{i}.csv")), None);
+        }
+
+        assert_eq!(recent.files().len(), MAX_RECENT_FILES);
+        assert_eq!(
+            recent.files()[0].path,
+            Path::new(&format!("/nonexistent/{}.csv", MAX_RECENT_FILES + 4))
+        );
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let path = temp_path("recent.toml");
+
+        let mut recent = RecentFiles::default();
+        recent.add(Path::new("/nonexistent/a.csv"), Some("a"));
+        appconfig::save_toml(&recent, &path, "recent files").unwrap();
+
+        let loaded: RecentFiles = appconfig::load_toml(&path, "recent files").unwrap();
+        assert_eq!(loaded.files(), recent.files());
+
+        fs::remove_file(path).ok();
+    }
+}
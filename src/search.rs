@@ -0,0 +1,106 @@
+use polars::prelude::{DataType, Expr, Schema, col, lit};
+use std::ops::Range;
+
+/// Expression that keeps the rows where any of the columns contains the search text
+/// The search is case insensitive and matches the text representation of the values
+/// Returns `None` when there is nothing to search for
+pub fn search_expr(schema: &Schema, text: &str) -> Option<Expr> {
+    if text.is_empty() {
+        return None;
+    }
+
+    let text = text.to_lowercase();
+    schema
+        .iter()
+        // Nested values have no text representation that can be searched
+        .filter(|(_, dtype)| !dtype.is_nested())
+        .map(|(name, _)| {
+            col(name.clone())
+                .cast(DataType::String)
+                .str()
+                .to_lowercase()
+                .str()
+                .contains_literal(lit(text.clone()))
+                .fill_null(lit(false))
+        })
+        .reduce(|matches, column_matches| matches.or(column_matches))
+}
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// The byte ranges of the case insensitive occurrences of the search text in a value
+pub fn match_ranges(value: &str, text: &str) -> Vec<Range<usize>> {
+    let needle: Vec<char> = text.chars().map(lowercase).collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let chars: Vec<(usize, char)> = value.char_indices().collect();
+    let mut ranges = Vec::new();
+    let mut index = 0;
+    while index + needle.len() <= chars.len() {
+        let candidate = &chars[index..index + needle.len()];
+        if candidate
+            .iter()
+            .zip(&needle)
+            .all(|((_, c), n)| lowercase(*c) == *n)
+        {
+            let start = chars[index].0;
+            let end = chars
+                .get(index + needle.len())
+                .map_or(value.len(), |(offset, _)| *offset);
+            ranges.push(start..end);
+            index += needle.len();
+        } else {
+            index += 1;
+        }
+    }
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+
+    #[test]
+    fn test_search_matches_any_column() {
+        let data = df!(
+            "name" => ["Alice", "Bob", "Charlie"],
+            "city" => [Some("Gent"), None, Some("Brussel")],
+            "age" => [31, 42, 25],
+        )
+        .unwrap();
+
+        let filtered = data
+            .clone()
+            .lazy()
+            .filter(search_expr(data.schema(), "BR").unwrap())
+            .collect()
+            .unwrap();
+        assert_eq!(filtered.height(), 1);
+
+        let filtered = data
+            .clone()
+            .lazy()
+            .filter(search_expr(data.schema(), "4").unwrap())
+            .collect()
+            .unwrap();
+        assert_eq!(filtered.height(), 1);
+
+        assert!(search_expr(data.schema(), "").is_none());
+    }
+
+    #[test]
+    fn test_match_ranges() {
+        assert_eq!(match_ranges("Banana", "an"), [1..3, 3..5]);
+        assert_eq!(match_ranges("Banana", "AN"), [1..3, 3..5]);
+        assert_eq!(match_ranges("aaa", "aa"), [0..2]);
+        assert_eq!(match_ranges("Überlingen", "über"), [0..5]);
+        assert!(match_ranges("Banana", "x").is_empty());
+        assert!(match_ranges("Banana", "").is_empty());
+    }
+}
@@ -0,0 +1,331 @@
+use polars::frame::DataFrame;
+use polars::prelude::AnyValue;
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// A rectangular range of cells, the anchor is the cell where the selection started
+/// Row and column selections are ranges that span all the columns or rows of the table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    anchor: (usize, usize),
+    /// The opposite corner of the range, included in the selection
+    focus: (usize, usize),
+}
+
+impl Selection {
+    pub fn cell(row_ix: usize, col_ix: usize) -> Self {
+        Self {
+            anchor: (row_ix, col_ix),
+            focus: (row_ix, col_ix),
+        }
+    }
+
+    /// The cells of the column, `row_count` is the number of rows of the table
+    pub fn column(col_ix: usize, row_count: usize) -> Self {
+        Self {
+            anchor: (0, col_ix),
+            focus: (row_count.saturating_sub(1), col_ix),
+        }
+    }
+
+    /// Move the corner opposite of the anchor to the cell
+    pub fn extend_to(&mut self, row_ix: usize, col_ix: usize) {
+        self.focus = (row_ix, col_ix);
+    }
+
+    pub fn rows(&self) -> Range<usize> {
+        self.anchor.0.min(self.focus.0)..self.anchor.0.max(self.focus.0) + 1
+    }
+
+    pub fn columns(&self) -> Range<usize> {
+        self.anchor.1.min(self.focus.1)..self.anchor.1.max(self.focus.1) + 1
+    }
+
+    pub fn contains(&self, row_ix: usize, col_ix: usize) -> bool {
+        self.rows().contains(&row_ix) && self.columns().contains(&col_ix)
+    }
+
+    /// Extend the selection to all the columns of the selected rows
+    pub fn full_rows(&self, col_count: usize) -> Self {
+        let rows = self.rows();
+        Self {
+            anchor: (rows.start, 0),
+            focus: (rows.end - 1, col_count.saturating_sub(1)),
+        }
+    }
+
+    /// Extend the selection to all the rows of the selected columns
+    pub fn full_columns(&self, row_count: usize) -> Self {
+        let columns = self.columns();
+        Self {
+            anchor: (0, columns.start),
+            focus: (row_count.saturating_sub(1), columns.end - 1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyFormat {
+    /// Tab separated values without a header, pastes into spreadsheets
+    Tsv,
+    Csv,
+    Markdown,
+    /// An array of objects, one object per row
+    Json,
+    /// The distinct values as an SQL `IN (...)` list, NULL never matches so it is left out
+    SqlIn,
+}
+
+impl CopyFormat {
+    pub const ALL: &[CopyFormat] = &[
+        CopyFormat::Tsv,
+        CopyFormat::Csv,
+        CopyFormat::Markdown,
+        CopyFormat::Json,
+        CopyFormat::SqlIn,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CopyFormat::Tsv => "TSV",
+            CopyFormat::Csv => "CSV",
+            CopyFormat::Markdown => "Markdown table",
+            CopyFormat::Json => "JSON records",
+            CopyFormat::SqlIn => "SQL IN list",
+        }
+    }
+}
+
+/// The text representation of a value as it is shown in the table, `None` for missing values
+fn value_text(value: &AnyValue) -> Option<String> {
+    match value {
+        AnyValue::Null => None,
+        AnyValue::String(str) => Some(str.to_string()),
+        AnyValue::StringOwned(str) => Some(str.to_string()),
+        value => Some(value.to_string()),
+    }
+}
+
+/// Quote the value when it contains the separator, a quote or a line break
+fn quote_delimited(text: &str, separator: char) -> String {
+    if text.contains([separator, '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn json_value(value: &AnyValue) -> serde_json::Value {
+    match value {
+        AnyValue::Null => serde_json::Value::Null,
+        AnyValue::Boolean(value) => serde_json::Value::Bool(*value),
+        value if value.dtype().is_float() => value
+            .extract::<f64>()
+            .map(serde_json::Value::from)
+            .unwrap_or_default(),
+        value if value.dtype().is_unsigned_integer() => value
+            .extract::<u64>()
+            .map(serde_json::Value::from)
+            .unwrap_or_default(),
+        value if value.dtype().is_signed_integer() => value
+            .extract::<i64>()
+            .map(serde_json::Value::from)
+            .unwrap_or_default(),
+        value => value_text(value)
+            .map(serde_json::Value::String)
+            .unwrap_or_default(),
+    }
+}
+
+fn sql_literal(value: &AnyValue) -> String {
+    match value {
+        AnyValue::Null => "NULL".to_string(),
+        AnyValue::Boolean(value) => value.to_string().to_uppercase(),
+        value if value.is_primitive_numeric() => value.to_string(),
+        value => format!(
+            "'{}'",
+            value_text(value).unwrap_or_default().replace('\'', "''")
+        ),
+    }
+}
+
+/// Format the selected cells of the data, the selection is clipped to the size of the data
+pub fn format_selection(data: &DataFrame, selection: &Selection, format: CopyFormat) -> String {
+    let clip = |range: Range<usize>, len: usize| range.start.min(len)..range.end.min(len);
+    let rows = clip(selection.rows(), data.height());
+    let columns = &data.get_columns()[clip(selection.columns(), data.width())];
+
+    let row_values = rows.map(|row_ix| {
+        columns
+            .iter()
+            .map(|column| column.get(row_ix).unwrap_or(AnyValue::Null))
+            .collect::<Vec<_>>()
+    });
+
+    match format {
+        CopyFormat::Tsv | CopyFormat::Csv => {
+            let separator = if format == CopyFormat::Tsv { '\t' } else { ',' };
+            row_values
+                .map(|values| {
+                    values
+                        .iter()
+                        .map(|value| {
+                            quote_delimited(&value_text(value).unwrap_or_default(), separator)
+                        })
+                        .collect::<Vec<_>>()
+                        .join(&separator.to_string())
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        CopyFormat::Markdown => {
+            let escape = |text: &str| text.replace('|', "\\|").replace(['\n', '\r'], " ");
+            let mut lines = vec![
+                format!(
+                    "| {} |",
+                    columns
+                        .iter()
+                        .map(|column| escape(column.name()))
+                        .collect::<Vec<_>>()
+                        .join(" | ")
+                ),
+                format!("|{}", "---|".repeat(columns.len())),
+            ];
+            lines.extend(row_values.map(|values| {
+                format!(
+                    "| {} |",
+                    values
+                        .iter()
+                        .map(|value| escape(&value_text(value).unwrap_or_default()))
+                        .collect::<Vec<_>>()
+                        .join(" | ")
+                )
+            }));
+            lines.join("\n")
+        }
+        CopyFormat::Json => {
+            let records = row_values
+                .map(|values| {
+                    let record = columns
+                        .iter()
+                        .zip(&values)
+                        .map(|(column, value)| (column.name().to_string(), json_value(value)))
+                        .collect::<serde_json::Map<_, _>>();
+                    serde_json::Value::Object(record)
+                })
+                .collect::<Vec<_>>();
+            serde_json::to_string_pretty(&records).unwrap_or_default()
+        }
+        CopyFormat::SqlIn => {
+            let mut seen = HashSet::new();
+            let mut literals = Vec::new();
+            for values in row_values {
+                let values = values.iter().filter(|value| !value.is_null());
+                for literal in values.map(sql_literal) {
+                    if seen.insert(literal.clone()) {
+                        literals.push(literal);
+                    }
+                }
+            }
+            format!("IN ({})", literals.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::people;
+
+    #[test]
+    fn test_selection_ranges() {
+        let mut selection = Selection::cell(4, 3);
+        selection.extend_to(2, 5);
+        assert_eq!(selection.rows(), 2..5);
+        assert_eq!(selection.columns(), 3..6);
+        assert!(selection.contains(3, 4));
+        assert!(!selection.contains(1, 4));
+
+        let rows = selection.full_rows(10);
+        assert_eq!(rows.rows(), 2..5);
+        assert_eq!(rows.columns(), 0..10);
+
+        let columns = selection.full_columns(100);
+        assert_eq!(columns.rows(), 0..100);
+        assert_eq!(columns.columns(), 3..6);
+        assert_eq!(Selection::column(3, 100).rows(), 0..100);
+    }
+
+    #[test]
+    fn test_copy_tsv_and_csv() {
+        let mut selection = Selection::cell(2, 0);
+        selection.extend_to(4, 1);
+
+        assert_eq!(
+            format_selection(&people(), &selection, CopyFormat::Tsv),
+            "alicia\t\nCharlie\tAntwerpen\nO'Brien, Dave\tGent"
+        );
+        assert_eq!(
+            format_selection(&people(), &selection, CopyFormat::Csv),
+            "alicia,\nCharlie,Antwerpen\n\"O'Brien, Dave\",Gent"
+        );
+    }
+
+    #[test]
+    fn test_copy_markdown() {
+        let selection = Selection::cell(0, 0).full_rows(5);
+        assert_eq!(
+            format_selection(&people(), &selection, CopyFormat::Markdown),
+            "| name | city | age | score | active |\n|---|---|---|---|---|\n\
+             | Alice | Gent | 31 | 1.5 | true |"
+        );
+    }
+
+    #[test]
+    fn test_copy_json() {
+        let mut selection = Selection::cell(1, 1);
+        selection.extend_to(2, 3);
+        let json: serde_json::Value =
+            serde_json::from_str(&format_selection(&people(), &selection, CopyFormat::Json))
+                .unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"city": "Brussel", "age": 42, "score": 2.0},
+                {"city": null, "age": 25, "score": 3.25},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_copy_sql_in() {
+        let data = people();
+        assert_eq!(
+            format_selection(&data, &Selection::column(2, 5), CopyFormat::SqlIn),
+            "IN (31, 42, 25, 8)"
+        );
+        assert_eq!(
+            format_selection(&data, &Selection::column(1, 5), CopyFormat::SqlIn),
+            "IN ('Gent', 'Brussel', 'Antwerpen')"
+        );
+        assert_eq!(
+            format_selection(&data, &Selection::column(4, 5), CopyFormat::SqlIn),
+            "IN (TRUE, FALSE)"
+        );
+        assert_eq!(
+            format_selection(&data, &Selection::column(0, 5), CopyFormat::SqlIn),
+            "IN ('Alice', 'Bob', 'alicia', 'Charlie', 'O''Brien, Dave')"
+        );
+    }
+
+    #[test]
+    fn test_selection_is_clipped_to_data() {
+        let mut selection = Selection::cell(4, 3);
+        selection.extend_to(10, 10);
+        assert_eq!(
+            format_selection(&people(), &selection, CopyFormat::Tsv),
+            "4.75\tfalse"
+        );
+    }
+}
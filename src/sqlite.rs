@@ -0,0 +1,455 @@
+use anyhow::{Context, Result};
+use polars::frame::DataFrame;
+use polars::prelude::{
+    AnonymousScan, AnonymousScanArgs, Column, DataType, LazyFrame, NamedFrom, PlSmallStr,
+    PolarsError, PolarsResult, ScanArgsAnonymous, Schema, Series,
+};
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use std::any::Any;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The rows are converted to columns in batches, which bounds the memory of the intermediate values
+const BATCH_ROWS: usize = 50_000;
+
+/// The tables of a GeoPackage that describe the layers instead of containing data
+const GEOPACKAGE_PREFIXES: &[&str] = &["gpkg_", "rtree_"];
+
+fn open(path: &Path) -> Result<Connection> {
+    Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .with_context(|| format!("Failed to open database: {}", path.display()))
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// The tables and views of the database, internal tables are skipped
+pub fn table_names(path: &Path) -> Result<Vec<String>> {
+    let connection = open(path)?;
+    let mut statement = connection.prepare(
+        "SELECT name FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name",
+    )?;
+
+    let names = statement
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(names
+        .into_iter()
+        .filter(|name| {
+            !GEOPACKAGE_PREFIXES
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+        })
+        .collect())
+}
+
+/// The values of a column in a batch, SQLite columns can contain values of different types
+/// so the column type is widened when a value does not fit
+enum ColumnValues {
+    Int(Vec<Option<i64>>),
+    Float(Vec<Option<f64>>),
+    Text(Vec<Option<String>>),
+}
+
+impl ColumnValues {
+    fn push(&mut self, value: ValueRef) {
+        match (&mut *self, value) {
+            (ColumnValues::Int(values), ValueRef::Null) => values.push(None),
+            (ColumnValues::Float(values), ValueRef::Null) => values.push(None),
+            (ColumnValues::Text(values), ValueRef::Null) => values.push(None),
+            (ColumnValues::Int(values), ValueRef::Integer(value)) => values.push(Some(value)),
+            (ColumnValues::Float(values), ValueRef::Integer(value)) => {
+                values.push(Some(value as f64))
+            }
+            (ColumnValues::Float(values), ValueRef::Real(value)) => values.push(Some(value)),
+            (ColumnValues::Text(values), value) => values.push(Some(value_text(value))),
+            (ColumnValues::Int(values), ValueRef::Real(value)) => {
+                let mut floats: Vec<_> = values.iter().map(|v| v.map(|v| v as f64)).collect();
+                floats.push(Some(value));
+                *self = ColumnValues::Float(floats);
+            }
+            (_, value) => {
+                let mut texts = self.to_text();
+                texts.push(Some(value_text(value)));
+                *self = ColumnValues::Text(texts);
+            }
+        }
+    }
+
+    fn to_text(&self) -> Vec<Option<String>> {
+        match self {
+            ColumnValues::Int(values) => values.iter().map(|v| v.map(|v| v.to_string())).collect(),
+            ColumnValues::Float(values) => {
+                values.iter().map(|v| v.map(|v| v.to_string())).collect()
+            }
+            ColumnValues::Text(values) => values.clone(),
+        }
+    }
+
+    fn into_series(self, name: &str) -> Series {
+        match self {
+            ColumnValues::Int(values) => Series::new(name.into(), values),
+            ColumnValues::Float(values) => Series::new(name.into(), values),
+            ColumnValues::Text(values) => Series::new(name.into(), values),
+        }
+    }
+}
+
+fn value_text(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => String::new(),
+        ValueRef::Integer(value) => value.to_string(),
+        ValueRef::Real(value) => value.to_string(),
+        ValueRef::Text(text) => String::from_utf8_lossy(text).to_string(),
+        // Binary values like GeoPackage geometries cannot be shown as text
+        ValueRef::Blob(blob) => format!("<{} bytes>", blob.len()),
+    }
+}
+
+/// Widen the column types of the batches so they can be stacked
+fn unify_column_types(batches: &mut [DataFrame]) -> Result<()> {
+    let Some(first) = batches.first() else {
+        return Ok(());
+    };
+
+    for col_ix in 0..first.width() {
+        let dtypes: Vec<DataType> = batches
+            .iter()
+            .map(|batch| batch.get_columns()[col_ix].dtype().clone())
+            .collect();
+
+        let target = if dtypes.contains(&DataType::String) {
+            DataType::String
+        } else if dtypes.contains(&DataType::Float64) {
+            DataType::Float64
+        } else {
+            continue;
+        };
+
+        for batch in batches.iter_mut() {
+            let column = &batch.get_columns()[col_ix];
+            if column.dtype() != &target {
+                let casted = column.cast(&target)?;
+                batch.replace_column(col_ix, casted)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Read the table or view into memory, at most `row_limit` rows are read when a limit is given
+/// Returns the data and whether the limit left out rows of the table
+pub fn read_table(path: &Path, table: &str, row_limit: Option<usize>) -> Result<(DataFrame, bool)> {
+    read_columns(path, table, None, row_limit)
+}
+
+/// Read the columns of the table or view, all the columns are read when `columns` is not set
+fn read_columns(
+    path: &Path,
+    table: &str,
+    columns: Option<&[PlSmallStr]>,
+    row_limit: Option<usize>,
+) -> Result<(DataFrame, bool)> {
+    let connection = open(path)?;
+    let selection = match columns {
+        Some(columns) => columns
+            .iter()
+            .map(|column| quote_identifier(column))
+            .collect::<Vec<_>>()
+            .join(", "),
+        None => "*".to_string(),
+    };
+    let mut query = format!("SELECT {selection} FROM {}", quote_identifier(table));
+    // One extra row is read to know if the table was truncated
+    if let Some(limit) = row_limit {
+        query.push_str(&format!(" LIMIT {}", limit.saturating_add(1)));
+    }
+
+    let mut statement = connection
+        .prepare(&query)
+        .with_context(|| format!("Failed to read table {table}"))?;
+    let names: Vec<String> = statement
+        .column_names()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+
+    let new_batch = || -> Vec<ColumnValues> {
+        names
+            .iter()
+            .map(|_| ColumnValues::Int(Vec::with_capacity(BATCH_ROWS)))
+            .collect()
+    };
+    let finish_batch = |batch: Vec<ColumnValues>| -> Result<DataFrame> {
+        let columns = batch
+            .into_iter()
+            .zip(&names)
+            .map(|(values, name)| Column::from(values.into_series(name)))
+            .collect();
+        Ok(DataFrame::new(columns)?)
+    };
+
+    let mut batches = Vec::new();
+    let mut batch = new_batch();
+    let mut batch_rows = 0;
+    let mut row_count = 0;
+    let mut truncated = false;
+    let mut rows = statement.query([])?;
+    while let Some(row) = rows.next()? {
+        if row_limit.is_some_and(|limit| row_count == limit) {
+            truncated = true;
+            break;
+        }
+        row_count += 1;
+
+        for (col_ix, values) in batch.iter_mut().enumerate() {
+            values.push(row.get_ref(col_ix)?);
+        }
+
+        batch_rows += 1;
+        if batch_rows == BATCH_ROWS {
+            batches.push(finish_batch(std::mem::replace(&mut batch, new_batch()))?);
+            batch_rows = 0;
+        }
+    }
+    if batch_rows > 0 || batches.is_empty() {
+        batches.push(finish_batch(batch)?);
+    }
+
+    unify_column_types(&mut batches)?;
+    let mut batches = batches.into_iter();
+    let mut data = batches.next().unwrap_or_default();
+    for batch in batches {
+        data.vstack_mut(&batch)?;
+    }
+    data.align_chunks_par();
+
+    Ok((data, truncated))
+}
+
+/// The column types of the table or view, they follow from the values of the complete table
+/// in the same way as the types of the columns that are read
+fn table_schema(connection: &Connection, table: &str) -> Result<Schema> {
+    let table = quote_identifier(table);
+    let names: Vec<String> = connection
+        .prepare(&format!("SELECT * FROM {table} LIMIT 0"))?
+        .column_names()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+
+    let mut fields = Vec::with_capacity(names.len());
+    for name in names {
+        let mut statement = connection.prepare(&format!(
+            "SELECT DISTINCT typeof({}) FROM {table}",
+            quote_identifier(&name)
+        ))?;
+        let types = statement
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let dtype = if types.iter().any(|t| t == "text" || t == "blob") {
+            DataType::String
+        } else if types.iter().any(|t| t == "real") {
+            DataType::Float64
+        } else {
+            DataType::Int64
+        };
+        fields.push((PlSmallStr::from(name), dtype));
+    }
+
+    Ok(Schema::from_iter(fields))
+}
+
+/// Reads the rows of a table when a lazy frame of the table is collected
+struct TableScan {
+    path: PathBuf,
+    table: String,
+}
+
+impl TableScan {
+    fn read(&self, args: AnonymousScanArgs) -> Result<DataFrame> {
+        let columns = match &args.with_columns {
+            Some(columns) => columns.to_vec(),
+            None => args.schema.iter_names().cloned().collect(),
+        };
+
+        if columns.is_empty() {
+            // Only the number of rows is used, e.g. by `SELECT COUNT(*)`
+            let connection = open(&self.path)?;
+            let count: usize = connection.query_row(
+                &format!("SELECT COUNT(*) FROM {}", quote_identifier(&self.table)),
+                [],
+                |row| row.get(0),
+            )?;
+            return Ok(DataFrame::empty_with_height(
+                args.n_rows.map_or(count, |n_rows| count.min(n_rows)),
+            ));
+        }
+
+        let (data, _) = read_columns(&self.path, &self.table, Some(&columns), args.n_rows)?;
+        // The values of a part of the rows can fit in a narrower type than the column type
+        let columns = data
+            .take_columns()
+            .into_iter()
+            .map(|column| match args.schema.get(column.name()) {
+                Some(dtype) if dtype != column.dtype() => column.cast(dtype),
+                _ => Ok(column),
+            })
+            .collect::<PolarsResult<Vec<_>>>()?;
+        Ok(DataFrame::new(columns)?)
+    }
+}
+
+impl AnonymousScan for TableScan {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn scan(&self, args: AnonymousScanArgs) -> PolarsResult<DataFrame> {
+        self.read(args)
+            .map_err(|err| PolarsError::ComputeError(format!("{err:#}").into()))
+    }
+
+    fn allows_projection_pushdown(&self) -> bool {
+        true
+    }
+
+    fn allows_slice_pushdown(&self) -> bool {
+        true
+    }
+}
+
+/// Scan the table or view lazily, nothing is read until the frame is collected
+/// and then only the columns and rows that are used, e.g. by a SQL query
+pub fn scan_table(path: &Path, table: &str) -> Result<LazyFrame> {
+    let schema = table_schema(&open(path)?, table)
+        .with_context(|| format!("Failed to read table {table}"))?;
+    let scan = TableScan {
+        path: path.to_path_buf(),
+        table: table.to_string(),
+    };
+
+    Ok(LazyFrame::anonymous_scan(
+        Arc::new(scan),
+        ScanArgsAnonymous {
+            schema: Some(Arc::new(schema)),
+            name: "sqlite",
+            ..Default::default()
+        },
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::temp_path;
+    use polars::prelude::col;
+
+    fn create_database(name: &str) -> std::path::PathBuf {
+        let path = temp_path(name);
+        std::fs::remove_file(&path).ok();
+
+        let connection = Connection::open(&path).unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE people (id INTEGER, name TEXT, score REAL);
+                 INSERT INTO people VALUES (1, 'Alice', 1.5), (2, 'Bob', NULL), (3, NULL, 3);
+                 CREATE TABLE mixed (value);
+                 INSERT INTO mixed VALUES (1), (2.5), ('text'), (x'0102');
+                 CREATE VIEW adults AS SELECT * FROM people WHERE id > 1;
+                 CREATE TABLE gpkg_contents (table_name TEXT);",
+            )
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_table_names() {
+        let path = create_database("tables.gpkg");
+        assert_eq!(table_names(&path).unwrap(), ["adults", "mixed", "people"]);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_read_table() {
+        let path = create_database("read.sqlite");
+
+        let (data, truncated) = read_table(&path, "people", None).unwrap();
+        assert!(!truncated);
+        assert_eq!(data.shape(), (3, 3));
+        assert_eq!(data.column("id").unwrap().dtype(), &DataType::Int64);
+        assert_eq!(data.column("score").unwrap().dtype(), &DataType::Float64);
+        assert_eq!(data.column("name").unwrap().null_count(), 1);
+
+        let (data, truncated) = read_table(&path, "adults", Some(1)).unwrap();
+        assert!(truncated);
+        assert_eq!(data.shape(), (1, 3));
+
+        // A limit that equals the number of rows does not truncate the table
+        let (data, truncated) = read_table(&path, "adults", Some(2)).unwrap();
+        assert!(!truncated);
+        assert_eq!(data.shape(), (2, 3));
+
+        let (data, _) = read_table(&path, "mixed", None).unwrap();
+        let values: Vec<_> = data
+            .column("value")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(
+            values,
+            [Some("1"), Some("2.5"), Some("text"), Some("<2 bytes>")]
+        );
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_scan_table() {
+        let path = create_database("scan.sqlite");
+
+        let data = scan_table(&path, "people").unwrap().collect().unwrap();
+        assert_eq!(data, read_table(&path, "people", None).unwrap().0);
+
+        // A part of the rows has the type of the complete column
+        let data = scan_table(&path, "mixed")
+            .unwrap()
+            .limit(1)
+            .collect()
+            .unwrap();
+        assert_eq!(data.column("value").unwrap().dtype(), &DataType::String);
+
+        let data = scan_table(&path, "adults")
+            .unwrap()
+            .select([col("name")])
+            .collect()
+            .unwrap();
+        assert_eq!(data.shape(), (2, 1));
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_unify_batch_types() {
+        let mut batches = vec![
+            DataFrame::new(vec![Column::new("a".into(), [1i64, 2])]).unwrap(),
+            DataFrame::new(vec![Column::new("a".into(), [1.5f64])]).unwrap(),
+        ];
+        unify_column_types(&mut batches).unwrap();
+        assert!(
+            batches
+                .iter()
+                .all(|batch| batch.column("a").unwrap().dtype() == &DataType::Float64)
+        );
+    }
+}
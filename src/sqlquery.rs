@@ -0,0 +1,110 @@
+use polars::{
+    frame::DataFrame,
+    prelude::{LazyFrame, PolarsResult},
+    sql::SQLContext,
+};
+
+/// Run a SQL query where every table is available under its name
+/// Only the columns and rows the query needs are read from lazily scanned tables
+pub fn run_query(tables: Vec<(String, LazyFrame)>, query: &str) -> PolarsResult<DataFrame> {
+    let mut context = SQLContext::new();
+    for (name, data) in tables {
+        context.register(&name, data);
+    }
+
+    context.execute(query)?.collect()
+}
+
+/// The table names that occur in the query, only these tables have to be loaded
+pub fn referenced_tables<'a>(
+    query: &str,
+    tables: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    let query = query.to_lowercase();
+    tables
+        .into_iter()
+        .filter(|table| contains_identifier(&query, &table.to_lowercase()))
+        .collect()
+}
+
+/// Whether the name occurs as a whole identifier, e.g. `sheet1` does not occur in `sheet10`
+fn contains_identifier(text: &str, name: &str) -> bool {
+    let is_identifier_char = |c: char| c.is_alphanumeric() || c == '_';
+    !name.is_empty()
+        && text.match_indices(name).any(|(start, _)| {
+            let before = text[..start].chars().next_back();
+            let after = text[start + name.len()..].chars().next();
+            !before.is_some_and(is_identifier_char) && !after.is_some_and(is_identifier_char)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+
+    fn tables() -> Vec<(String, LazyFrame)> {
+        vec![
+            (
+                "Sheet1".to_string(),
+                df!(
+                    "id" => [1, 2, 3],
+                    "name" => ["Alice", "Bob", "Charlie"],
+                )
+                .unwrap()
+                .lazy(),
+            ),
+            (
+                "Sheet2".to_string(),
+                df!(
+                    "id" => [1, 3],
+                    "city" => ["Gent", "Antwerpen"],
+                )
+                .unwrap()
+                .lazy(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_join_tables() {
+        let result = run_query(
+            tables(),
+            "SELECT Sheet1.name, Sheet2.city FROM Sheet1 JOIN Sheet2 ON Sheet1.id = Sheet2.id ORDER BY Sheet1.id",
+        )
+        .unwrap();
+
+        assert_eq!(result.shape(), (2, 2));
+        let names: Vec<_> = result
+            .column("name")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(names, [Some("Alice"), Some("Charlie")]);
+    }
+
+    #[test]
+    fn test_invalid_query() {
+        assert!(run_query(tables(), "SELECT * FROM Sheet3").is_err());
+        assert!(run_query(tables(), "SELEC * FROM Sheet1").is_err());
+    }
+
+    #[test]
+    fn test_referenced_tables() {
+        let tables = ["Sheet1", "Sheet2", "Totals"];
+        assert_eq!(
+            referenced_tables("select * from sheet1 join Totals using (id)", tables),
+            ["Sheet1", "Totals"]
+        );
+
+        // Only whole identifiers match
+        let tables = ["Sheet1", "a", "My Sheet"];
+        assert!(referenced_tables("SELECT name FROM Sheet10", tables).is_empty());
+        assert_eq!(
+            referenced_tables("SELECT * FROM a JOIN \"My Sheet\" USING (id)", tables),
+            ["a", "My Sheet"]
+        );
+    }
+}
@@ -1,25 +1,497 @@
-use anyhow::Result;
-use gpui::SharedString;
+use anyhow::{Context, Result, bail};
+use gpui::{Global, SharedString};
+use polars::frame::DataFrame;
+use polars::prelude::{
+    HiveOptions, IntoLazy, IpcReader, JsonFormat, JsonReader, LazyFrame, ParquetReader, PlPath,
+    ScanArgsParquet, SerReader,
+};
+use std::fs::{self, File};
+use std::io::Read;
 use std::path::Path;
 
 use geo::vector::dataframe::{DataFrameOptions, HeaderRow, create_dataframe_reader};
 
+use crate::csvdialect::{self, CsvOptions};
+use crate::sqlite;
+use crate::textencoding::TextEncoding;
+
+/// File extensions (lowercase) of the table formats that can be opened
+pub const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "csv", "tsv", "xlsx", "parquet", "pq", "arrow", "ipc", "feather", "json", "ndjson", "jsonl",
+    "sqlite", "sqlite3", "db", "gpkg",
+];
+
+const PARQUET_MAGIC: &[u8] = b"PAR1";
+const IPC_MAGIC: &[u8] = b"ARROW1";
+const SQLITE_MAGIC: &[u8] = b"SQLite format 3\0";
+
+/// Options that apply to the layers that are read from a file
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    /// The maximum number of rows that is read from a database table, all rows when `None`
+    pub row_limit: Option<usize>,
+    /// The dialect of a delimited text file, it is detected from the start of the file when not set
+    pub csv: Option<CsvOptions>,
+    /// The encoding of delimited text files without csv options, it is detected when not set
+    pub encoding: Option<TextEncoding>,
+}
+
+impl Global for ReadOptions {}
+
+/// The reader that is used for a table file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TableFormat {
+    /// The formats that are read using the geo vector io
+    Vector,
+    /// Delimited text, the dialect is detected or chosen by the user
+    Csv,
+    Parquet,
+    /// A directory of (hive partitioned) parquet files that is read as a single table
+    ParquetDirectory,
+    Ipc,
+    /// A JSON array of objects
+    Json,
+    /// One JSON object per line
+    Ndjson,
+    /// A SQLite database or GeoPackage, every table and view is a layer
+    Sqlite,
+}
+
+fn format_for_extension(ext: &str) -> Option<TableFormat> {
+    match ext.to_ascii_lowercase().as_str() {
+        "csv" | "tsv" => Some(TableFormat::Csv),
+        "xlsx" => Some(TableFormat::Vector),
+        "parquet" | "pq" => Some(TableFormat::Parquet),
+        "arrow" | "ipc" | "feather" => Some(TableFormat::Ipc),
+        "json" => Some(TableFormat::Json),
+        "ndjson" | "jsonl" => Some(TableFormat::Ndjson),
+        "sqlite" | "sqlite3" | "db" | "gpkg" => Some(TableFormat::Sqlite),
+        _ => None,
+    }
+}
+
+/// Recognize parquet, arrow and SQLite files without a known extension by their magic bytes
+fn format_for_magic_bytes(path: &Path) -> Option<TableFormat> {
+    let mut header = Vec::with_capacity(SQLITE_MAGIC.len());
+    File::open(path)
+        .ok()?
+        .take(SQLITE_MAGIC.len() as u64)
+        .read_to_end(&mut header)
+        .ok()?;
+
+    if header.starts_with(PARQUET_MAGIC) {
+        Some(TableFormat::Parquet)
+    } else if header.starts_with(IPC_MAGIC) {
+        Some(TableFormat::Ipc)
+    } else if header.starts_with(SQLITE_MAGIC) {
+        Some(TableFormat::Sqlite)
+    } else {
+        None
+    }
+}
+
+fn is_parquet_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(format_for_extension)
+        == Some(TableFormat::Parquet)
+}
+
+fn contains_parquet_files(dir: &Path) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+
+    entries.flatten().any(|entry| {
+        let path = entry.path();
+        if path.is_dir() {
+            contains_parquet_files(&path)
+        } else {
+            is_parquet_file(&path)
+        }
+    })
+}
+
+fn parquet_files_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+
+    entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                parquet_files_size(&path)
+            } else if is_parquet_file(&path) {
+                entry.metadata().map(|meta| meta.len()).unwrap_or_default()
+            } else {
+                0
+            }
+        })
+        .sum()
+}
+
+/// The size of the file in bytes, for a directory the size of the parquet files it contains
+pub fn data_size(path: &Path) -> Option<u64> {
+    if path.is_dir() {
+        Some(parquet_files_size(path))
+    } else {
+        fs::metadata(path).ok().map(|meta| meta.len())
+    }
+}
+
+fn table_format(path: &Path) -> Option<TableFormat> {
+    if path.is_dir() {
+        return contains_parquet_files(path).then_some(TableFormat::ParquetDirectory);
+    }
+
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(format_for_extension)
+        .or_else(|| format_for_magic_bytes(path))
+}
+
+pub fn is_supported_path(path: &Path) -> bool {
+    table_format(path).is_some()
+}
+
+/// Delimited text files can be reopened with other read options
+pub fn is_delimited_text(path: &Path) -> bool {
+    table_format(path) == Some(TableFormat::Csv)
+}
+
+pub fn check_supported_path(path: &Path) -> Result<()> {
+    if !is_supported_path(path) {
+        bail!(
+            "Unsupported file format, supported formats: {} or a directory of parquet files",
+            SUPPORTED_EXTENSIONS.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// The name of the single layer of a csv, parquet, arrow or json file
+fn single_layer_name(path: &Path) -> SharedString {
+    let name = if path.is_dir() {
+        path.file_name()
+    } else {
+        path.file_stem()
+    };
+
+    name.map(|name| name.to_string_lossy().to_string().into())
+        .unwrap_or_else(|| "data".into())
+}
+
 pub fn layers_for_path(path: &Path) -> Result<Vec<SharedString>> {
-    let reader = create_dataframe_reader(path)?;
-    Ok(reader.layer_names()?.iter().map(Into::into).collect())
+    match table_format(path) {
+        Some(
+            TableFormat::Csv
+            | TableFormat::Parquet
+            | TableFormat::ParquetDirectory
+            | TableFormat::Ipc
+            | TableFormat::Json
+            | TableFormat::Ndjson,
+        ) => Ok(vec![single_layer_name(path)]),
+        Some(TableFormat::Sqlite) => Ok(sqlite::table_names(path)?
+            .into_iter()
+            .map(Into::into)
+            .collect()),
+        Some(TableFormat::Vector) | None => {
+            let reader = create_dataframe_reader(path)?;
+            Ok(reader.layer_names()?.iter().map(Into::into).collect())
+        }
+    }
 }
 
-pub fn layer_data(path: &Path, layer: &str) -> Result<polars::frame::DataFrame> {
-    log::debug!("Read table: {}", path.file_name().unwrap().display());
-    let df = geo::vector::dataframe::polars::read_dataframe(
-        path,
-        &DataFrameOptions {
-            layer: Some(layer.to_string()),
-            header_row: HeaderRow::Auto,
-            ..Default::default()
-        },
-    )?;
+/// The dialect of the delimited text file, the chosen csv options or the detected dialect
+pub fn csv_options(path: &Path, options: &ReadOptions) -> Result<CsvOptions> {
+    match &options.csv {
+        Some(csv_options) => Ok(csv_options.clone()),
+        None => csvdialect::sniff_file(path, options.encoding),
+    }
+}
+
+/// The data of a layer, `truncated` is set when the row limit left out rows of the layer
+pub struct LayerData {
+    pub data: DataFrame,
+    pub truncated: bool,
+    /// The encoding a delimited text file was decoded with
+    pub encoding: Option<TextEncoding>,
+}
+
+pub fn layer_data(path: &Path, layer: &str, options: &ReadOptions) -> Result<DataFrame> {
+    read_layer(path, layer, options).map(|layer| layer.data)
+}
+
+pub fn read_layer(path: &Path, layer: &str, options: &ReadOptions) -> Result<LayerData> {
+    log::debug!("Read table: {}", path.display());
+    let mut truncated = false;
+    let mut encoding = None;
+    let df = match table_format(path) {
+        Some(TableFormat::Csv) => {
+            let (data, csv_encoding) = read_csv(path, options)?;
+            encoding = Some(csv_encoding);
+            data
+        }
+        Some(TableFormat::Parquet) => ParquetReader::new(open_file(path)?).finish()?,
+        Some(TableFormat::ParquetDirectory) => LazyFrame::scan_parquet(
+            PlPath::new(&path.to_string_lossy()),
+            ScanArgsParquet {
+                hive_options: HiveOptions::new_enabled(),
+                ..Default::default()
+            },
+        )?
+        .collect()?,
+        Some(TableFormat::Ipc) => IpcReader::new(open_file(path)?).finish()?,
+        Some(TableFormat::Json) => JsonReader::new(open_file(path)?)
+            .with_json_format(JsonFormat::Json)
+            .finish()?,
+        Some(TableFormat::Ndjson) => JsonReader::new(open_file(path)?)
+            .with_json_format(JsonFormat::JsonLines)
+            .finish()?,
+        Some(TableFormat::Sqlite) => {
+            let (data, table_truncated) = sqlite::read_table(path, layer, options.row_limit)?;
+            truncated = table_truncated;
+            data
+        }
+        Some(TableFormat::Vector) | None => geo::vector::dataframe::polars::read_dataframe(
+            path,
+            &DataFrameOptions {
+                layer: Some(layer.to_string()),
+                header_row: HeaderRow::Auto,
+                ..Default::default()
+            },
+        )?,
+    };
     log::debug!("Read table done");
 
-    Ok(df)
+    Ok(LayerData {
+        data: df,
+        truncated,
+        encoding,
+    })
+}
+
+/// Read a delimited text file, returns the data and the encoding it was decoded with
+/// A detected UTF-8 encoding is only based on the start of the file, when the rest of the file
+/// turns out not to be UTF-8 the encoding is detected again from the complete file
+fn read_csv(path: &Path, options: &ReadOptions) -> Result<(DataFrame, TextEncoding)> {
+    let csv = csv_options(path, options)?;
+    let detected = options.csv.is_none() && options.encoding.is_none();
+    match csvdialect::read_csv(path, &csv, None) {
+        Ok(data) => Ok((data, csv.encoding)),
+        Err(err) if detected && csv.encoding == TextEncoding::UTF_8 => {
+            let Some(encoding) = csvdialect::detect_non_utf8_encoding(path)? else {
+                return Err(err);
+            };
+            log::info!("{} is not UTF-8, read it as {encoding}", path.display());
+            let csv = csvdialect::sniff_file(path, Some(encoding))?;
+            Ok((csvdialect::read_csv(path, &csv, None)?, encoding))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// The layer as a lazy frame for a query, every row of the layer is included
+/// Database tables are scanned, so only the rows and columns the query uses are read
+pub fn scan_layer(path: &Path, layer: &str, options: &ReadOptions) -> Result<LazyFrame> {
+    if table_format(path) == Some(TableFormat::Sqlite) {
+        return sqlite::scan_table(path, layer);
+    }
+
+    let options = ReadOptions {
+        row_limit: None,
+        ..options.clone()
+    };
+    Ok(layer_data(path, layer, &options)?.lazy())
+}
+
+fn open_file(path: &Path) -> Result<File> {
+    File::open(path).with_context(|| format!("Failed to open {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::{people, temp_path};
+    use polars::prelude::*;
+
+    /// The layer name of a file that contains a single table
+    fn layer_name(path: &Path) -> SharedString {
+        path.file_stem()
+            .unwrap()
+            .to_string_lossy()
+            .to_string()
+            .into()
+    }
+
+    #[test]
+    fn test_read_parquet_and_ipc() {
+        let parquet = temp_path("read.parquet");
+        ParquetWriter::new(File::create(&parquet).unwrap())
+            .finish(&mut people())
+            .unwrap();
+        let ipc = temp_path("read.feather");
+        IpcWriter::new(File::create(&ipc).unwrap())
+            .finish(&mut people())
+            .unwrap();
+
+        for path in [&parquet, &ipc] {
+            assert!(is_supported_path(path));
+            let layers = layers_for_path(path).unwrap();
+            assert_eq!(layers, [layer_name(path)]);
+            assert_eq!(
+                layer_data(path, &layers[0], &ReadOptions::default()).unwrap(),
+                people()
+            );
+            fs::remove_file(path).ok();
+        }
+    }
+
+    #[test]
+    fn test_read_json_and_ndjson() {
+        let rows = [
+            r#"{"id": 1, "user": {"name": "Alice"}, "tags": ["a", "b"]}"#,
+            r#"{"id": 2, "user": {"name": "Bob"}, "tags": []}"#,
+        ];
+        let json = temp_path("read.json");
+        fs::write(&json, format!("[{}]", rows.join(","))).unwrap();
+        let ndjson = temp_path("read.ndjson");
+        fs::write(&ndjson, rows.join("\n")).unwrap();
+
+        for path in [&json, &ndjson] {
+            assert!(is_supported_path(path));
+            let layers = layers_for_path(path).unwrap();
+            assert_eq!(layers, [layer_name(path)]);
+
+            let data = layer_data(path, &layers[0], &ReadOptions::default()).unwrap();
+            assert_eq!(data.shape(), (2, 3));
+            assert!(matches!(
+                data.column("user").unwrap().dtype(),
+                DataType::Struct(_)
+            ));
+            assert!(matches!(
+                data.column("tags").unwrap().dtype(),
+                DataType::List(_)
+            ));
+            fs::remove_file(path).ok();
+        }
+    }
+
+    #[test]
+    fn test_read_csv_dialect() {
+        let path = temp_path("read_dialect.csv");
+        fs::write(&path, "name;score\nAlice;1,5\nBob;2,25\n").unwrap();
+        assert!(is_delimited_text(&path));
+
+        let data = layer_data(&path, &layer_name(&path), &ReadOptions::default()).unwrap();
+        assert_eq!(data.shape(), (2, 2));
+        assert_eq!(data.column("score").unwrap().dtype(), &DataType::Float64);
+
+        let options = ReadOptions {
+            csv: Some(CsvOptions {
+                header_row: None,
+                ..csvdialect::sniff_file(&path, None).unwrap()
+            }),
+            ..Default::default()
+        };
+        let data = layer_data(&path, &layer_name(&path), &options).unwrap();
+        assert_eq!(data.shape(), (3, 2));
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_read_csv_detects_encoding_of_complete_file() {
+        let path = temp_path("read_encoding.csv");
+        let mut bytes = "name\n".to_string().into_bytes();
+        bytes.extend("Alice\n".repeat(20_000).into_bytes());
+        bytes.extend_from_slice(b"Li\xe8ge\n");
+        fs::write(&path, bytes).unwrap();
+
+        let layer = read_layer(&path, &layer_name(&path), &ReadOptions::default()).unwrap();
+        assert_eq!(layer.encoding.map(TextEncoding::name), Some("windows-1252"));
+        assert_eq!(
+            layer.data.column("name").unwrap().get(20_000).unwrap(),
+            AnyValue::String("Liège")
+        );
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_detect_format_by_magic_bytes() {
+        let path = temp_path("magic.data");
+        ParquetWriter::new(File::create(&path).unwrap())
+            .finish(&mut people())
+            .unwrap();
+        assert_eq!(table_format(&path), Some(TableFormat::Parquet));
+        fs::remove_file(&path).ok();
+
+        fs::write(&path, "a,b\n1,2\n").unwrap();
+        assert!(!is_supported_path(&path));
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_read_sqlite_tables() {
+        let path = temp_path("layers.data");
+        fs::remove_file(&path).ok();
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE cities (name TEXT);
+                 INSERT INTO cities VALUES ('Gent'), ('Brussel'), ('Antwerpen');
+                 CREATE TABLE countries (name TEXT);",
+            )
+            .unwrap();
+
+        assert_eq!(table_format(&path), Some(TableFormat::Sqlite));
+        assert_eq!(
+            layers_for_path(&path).unwrap(),
+            [
+                SharedString::from("cities"),
+                SharedString::from("countries")
+            ]
+        );
+
+        let options = ReadOptions {
+            row_limit: Some(2),
+            ..Default::default()
+        };
+        let layer = read_layer(&path, "cities", &options).unwrap();
+        assert!(layer.truncated);
+        assert_eq!(layer.data.shape(), (2, 1));
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_read_hive_partitioned_directory() {
+        let dir = temp_path("hive");
+        fs::remove_dir_all(&dir).ok();
+        for year in [2023, 2024] {
+            let partition = dir.join(format!("year={year}"));
+            fs::create_dir_all(&partition).unwrap();
+            ParquetWriter::new(File::create(partition.join("part-0.parquet")).unwrap())
+                .finish(&mut people())
+                .unwrap();
+        }
+
+        assert!(is_supported_path(&dir));
+        let parquet_size = fs::metadata(dir.join("year=2023/part-0.parquet"))
+            .unwrap()
+            .len();
+        assert_eq!(data_size(&dir), Some(parquet_size * 2));
+        assert_eq!(
+            layers_for_path(&dir).unwrap(),
+            [SharedString::from(
+                dir.file_name().unwrap().to_string_lossy().to_string()
+            )]
+        );
+
+        let data = layer_data(&dir, &layer_name(&dir), &ReadOptions::default()).unwrap();
+        assert_eq!(data.shape(), (10, 6));
+        assert!(data.column("year").is_ok());
+        fs::remove_dir_all(&dir).ok();
+    }
 }
@@ -1,121 +1,786 @@
 use gpui::{App, IntoElement, Window};
 use polars::{
     frame::DataFrame,
-    prelude::{AnyValue, IntoLazy, PlSmallStr, col, lit},
+    prelude::{AnyValue, DataType, PolarsResult, Series},
 };
+use std::time::Duration;
 
+use gpui::prelude::FluentBuilder as _;
 use gpui::*;
 use gpui_component::{
-    ActiveTheme, Icon, IconName, Sizable, StyledExt,
+    ActiveTheme, Icon, IconName, Selectable, Sizable, StyledExt, WindowExt as _,
+    button::{Button, ButtonVariants},
+    checkbox::Checkbox,
+    h_flex,
     input::{Input, InputEvent, InputState},
+    menu::PopupMenu,
+    notification::Notification,
     table::{Column, ColumnSort, TableDelegate, TableState},
     tag::Tag,
+    tooltip::Tooltip,
+    v_flex,
 };
 
+use crate::filter::{ColumnFilter, FilterOptions};
+use crate::filterpresets::FilterPreset;
+use crate::selection::{CopyFormat, Selection};
+use crate::tabulite::{CopySelection, CopySelectionAs, DrillDown};
+use crate::valuefilter::{self, DistinctValues, MAX_DISTINCT_VALUES, ValueFilter};
+use crate::viewstate::ViewState;
+use crate::{nested, search, utils};
+
+/// The distinct values of a column that can be selected to filter the rows
+struct ValueChecklist {
+    col_ix: usize,
+    /// `None` while the values are being counted
+    values: Option<DistinctValues>,
+    search_input: Entity<InputState>,
+    _subscription: Subscription,
+    _task: Task<()>,
+}
+
 #[derive(Default)]
 pub struct TableLayer {
     data: polars::frame::DataFrame,
+    /// Incremented every time the visible data is replaced
+    data_version: usize,
     original_data: polars::frame::DataFrame,
     filter_enabled: bool,
+    view_state: ViewState,
     filter_inputs: Vec<Entity<InputState>>,
     input_subscriptions: Vec<Subscription>,
+    view_task: Option<Task<()>>,
+    /// A sort is being calculated on the background executor
+    sorting: bool,
+    /// The view is being updated on the background executor
+    updating: bool,
     columns: Vec<Column>,
+    /// The row and column of the search match that was navigated to
+    current_match: Option<(usize, usize)>,
+    value_checklist: Option<ValueChecklist>,
+    selection: Option<Selection>,
+}
+
+/// The data of a layer together with its filter and sort state
+/// Used to restore the layer when switching back to it
+#[derive(Clone, Default)]
+pub struct LayerSnapshot {
+    original_data: DataFrame,
+    data: DataFrame,
+    filter_enabled: bool,
+    view_state: ViewState,
+}
+
+impl LayerSnapshot {
+    pub fn new(data: DataFrame) -> Self {
+        Self {
+            original_data: data.clone(),
+            data,
+            ..Default::default()
+        }
+    }
+
+    /// The data of the layer without filters or sorting applied
+    pub fn original_data(&self) -> &DataFrame {
+        &self.original_data
+    }
+
+    /// The estimated memory usage, the filtered/sorted data is only counted if it is present
+    pub fn estimated_size(&self) -> usize {
+        let view_size = if self.view_state.is_empty() {
+            0
+        } else {
+            self.data.estimated_size()
+        };
+
+        self.original_data.estimated_size() + view_size
+    }
 }
 
 const NULL: &'static str = "null";
+const NULL_VALUE_LABEL: &str = "(null)";
+const FILTER_DEBOUNCE: Duration = Duration::from_millis(150);
 
 impl TableLayer {
     pub fn update_data(&mut self, data: polars::frame::DataFrame) {
+        self.view_task = None;
+        self.sorting = false;
+        self.updating = false;
+        self.current_match = None;
+        self.value_checklist = None;
+        self.selection = None;
         self.original_data = data.clone();
         self.data = data;
+        self.data_version += 1;
         self.create_column_info();
     }
 
+    pub fn snapshot(&self) -> LayerSnapshot {
+        LayerSnapshot {
+            original_data: self.original_data.clone(),
+            data: self.data.clone(),
+            filter_enabled: self.filter_enabled,
+            view_state: self.view_state.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: LayerSnapshot) {
+        self.update_data(snapshot.original_data);
+        self.data = snapshot.data;
+        self.filter_enabled = snapshot.filter_enabled;
+        self.view_state = snapshot.view_state;
+        self.update_column_sort();
+    }
+
+    /// Drop the loaded data together with the filter and sort state
+    pub fn clear(&mut self) {
+        self.view_task = None;
+        self.sorting = false;
+        self.updating = false;
+        self.current_match = None;
+        self.value_checklist = None;
+        self.selection = None;
+        self.data = DataFrame::default();
+        self.data_version += 1;
+        self.original_data = DataFrame::default();
+        self.columns.clear();
+        self.input_subscriptions.clear();
+        self.filter_inputs.clear();
+        self.view_state = ViewState::default();
+    }
+
     pub fn toggle_filter(&mut self) {
         self.filter_enabled = !self.filter_enabled;
     }
 
-    fn filter_data(&mut self, cx: &mut Context<TableState<Self>>) {
-        // Collect filter texts with column names
-        let filters: Vec<(String, String)> = self
-            .filter_inputs
+    /// The number of rows of the layer without filters applied
+    pub fn total_rows(&self) -> usize {
+        self.original_data.height()
+    }
+
+    /// The number of rows that remain after filtering
+    pub fn visible_rows(&self) -> usize {
+        self.data.height()
+    }
+
+    /// Changes whenever the visible data changes, e.g. when a filter is applied
+    pub fn data_version(&self) -> usize {
+        self.data_version
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.original_data.width()
+    }
+
+    /// True while the filtered or sorted view is calculated
+    pub fn is_busy(&self) -> bool {
+        self.updating || self.sorting
+    }
+
+    pub fn view_state(&self) -> &ViewState {
+        &self.view_state
+    }
+
+    pub fn selection(&self) -> Option<Selection> {
+        self.selection
+    }
+
+    /// Select a single cell, or extend the current selection to the cell
+    pub fn select_cell(&mut self, row_ix: usize, col_ix: usize, extend: bool) {
+        match &mut self.selection {
+            Some(selection) if extend => selection.extend_to(row_ix, col_ix),
+            _ => self.selection = Some(Selection::cell(row_ix, col_ix)),
+        }
+    }
+
+    /// Extend the current selection to the cell, returns false when nothing changed
+    fn extend_selection(&mut self, row_ix: usize, col_ix: usize) -> bool {
+        let Some(selection) = &mut self.selection else {
+            return false;
+        };
+
+        let previous = *selection;
+        selection.extend_to(row_ix, col_ix);
+        *selection != previous
+    }
+
+    pub fn select_column(&mut self, col_ix: usize) {
+        self.selection = Some(Selection::column(col_ix, self.data.height()));
+    }
+
+    /// Extend the selection to all the columns of the selected rows
+    pub fn select_rows(&mut self) {
+        let col_count = self.columns.len();
+        if let Some(selection) = &mut self.selection {
+            *selection = selection.full_rows(col_count);
+        }
+    }
+
+    /// Extend the selection to all the rows of the selected columns
+    pub fn select_columns(&mut self) {
+        let row_count = self.data.height();
+        if let Some(selection) = &mut self.selection {
+            *selection = selection.full_columns(row_count);
+        }
+    }
+
+    /// The filtered and sorted data as it is shown, the selection refers to its rows and columns
+    pub fn visible_data(&self) -> &DataFrame {
+        &self.data
+    }
+
+    pub fn column_name(&self, col_ix: usize) -> Option<SharedString> {
+        self.columns.get(col_ix).map(|column| column.name.clone())
+    }
+
+    /// The items of the list in the cell as a table, `None` when the cell does not contain a list
+    pub fn list_items(&self, row_ix: usize, col_ix: usize) -> PolarsResult<Option<DataFrame>> {
+        match self.data[col_ix].get(row_ix)? {
+            AnyValue::List(items) => nested::list_items(items).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Replace the struct column by a column per field
+    /// The filters and sort keys of the other columns are kept
+    fn expand_struct_column(
+        &mut self,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<TableState<Self>>,
+    ) {
+        let column = self.columns[col_ix].key.to_string();
+        match nested::expand_struct(&self.original_data, &column) {
+            Ok(expanded) => {
+                let view_state = self.view_state.with_schema(expanded.schema().clone());
+                self.update_data(expanded);
+                // The filter inputs are created with the filter text when the filter row is shown
+                self.view_state = view_state;
+                self.update_column_sort();
+                self.sorting = !self.view_state.sort.is_empty();
+                self.update_view(Duration::ZERO, cx);
+            }
+            Err(err) => {
+                let message = utils::error_message("Failed to expand the column", err.into());
+                window.push_notification(Notification::error(message), cx);
+            }
+        }
+    }
+
+    /// The filtered and sorted data with the columns in the order they are shown
+    /// Columns can be moved but not hidden, so every column is included
+    pub fn export_data(&self) -> PolarsResult<DataFrame> {
+        self.data
+            .select(self.columns.iter().map(|column| column.key.as_ref()))
+    }
+
+    /// The values of the column as they are currently shown, with filters and sorting applied
+    pub fn column_series(&self, col_ix: usize) -> Option<Series> {
+        self.data
+            .get_columns()
+            .get(col_ix)
+            .map(|column| column.as_materialized_series().clone())
+    }
+
+    /// Replace the column filters and sort keys with the ones of the preset
+    /// Columns of the preset that are not present in the data are ignored, the value
+    /// checklists and the search are not part of a preset and stay active
+    pub fn apply_preset(
+        &mut self,
+        preset: &FilterPreset,
+        window: &mut Window,
+        cx: &mut Context<TableState<Self>>,
+    ) {
+        let schema = self.original_data.schema().clone();
+        let has_column = |column: &str| schema.get(column).is_some();
+
+        let mut view_state = ViewState::new(schema.clone());
+        view_state.search = self.view_state.search.clone();
+        view_state.value_filters = self.view_state.value_filters.clone();
+        for filter in preset
+            .filters
             .iter()
-            .enumerate()
-            .filter_map(|(col_ix, input)| {
-                let filter_text = input.read(cx).value().to_string();
-                if !filter_text.is_empty() {
-                    let col_name = self
-                        .columns
-                        .get(col_ix)
-                        .map(|col| col.key.to_string())
-                        .unwrap_or_default();
-                    Some((col_name, filter_text))
+            .filter(|filter| has_column(&filter.column))
+        {
+            view_state.set_filter_options(&filter.column, filter.options);
+            view_state.set_filter(&filter.column, &filter.text);
+        }
+        view_state.sort = preset
+            .sort
+            .iter()
+            .filter(|key| has_column(&key.column))
+            .cloned()
+            .collect();
+        self.view_state = view_state;
+
+        // The inputs are created with the filter text when the filter row is shown
+        for (column, input) in self.columns.iter().zip(&self.filter_inputs) {
+            let text = self
+                .view_state
+                .filter(column.key.as_ref())
+                .map(|filter| filter.text().to_string())
+                .unwrap_or_default();
+            input.update(cx, |input, cx| input.set_value(text, window, cx));
+        }
+
+        if !preset.filters.is_empty() {
+            self.filter_enabled = true;
+        }
+        self.update_column_sort();
+        self.sorting = !self.view_state.sort.is_empty();
+        self.update_view(Duration::ZERO, cx);
+    }
+
+    pub fn search(&self) -> &str {
+        &self.view_state.search
+    }
+
+    /// Only show the rows that contain the text in any of the columns
+    pub fn set_search(&mut self, text: &str, cx: &mut Context<TableState<Self>>) {
+        if self.view_state.search == text {
+            return;
+        }
+
+        self.view_state.search = text.to_string();
+        self.current_match = None;
+        self.update_view(FILTER_DEBOUNCE, cx);
+    }
+
+    /// Move to the next (or previous) cell that contains the search text
+    /// Returns the row of the match so the table can be scrolled to it
+    pub fn select_next_match(&mut self, forward: bool) -> Option<usize> {
+        let (rows, cols) = self.data.shape();
+        let cells = rows * cols;
+        if self.view_state.search.is_empty() || cells == 0 {
+            return None;
+        }
+
+        let start = match self.current_match {
+            Some((row, col)) => row * cols + col,
+            // Start before the first cell, or after the last cell when searching backwards
+            None if forward => cells - 1,
+            None => 0,
+        };
+
+        // Every visible row contains a match, so this normally stops within a couple of rows
+        let found = (1..=cells)
+            .map(|step| {
+                if forward {
+                    (start + step) % cells
                 } else {
-                    None
+                    (start + cells - step) % cells
                 }
             })
-            .collect();
+            .map(|cell| (cell / cols, cell % cols))
+            .find(|&(row, col)| {
+                self.cell_text(row, col).is_some_and(|text| {
+                    !search::match_ranges(&text, &self.view_state.search).is_empty()
+                })
+            });
+
+        self.current_match = found;
+        found.map(|(row, _)| row)
+    }
 
-        if filters.is_empty() {
+    /// The text representation of a cell, `None` for missing values
+    fn cell_text(&self, row_ix: usize, col_ix: usize) -> Option<SharedString> {
+        match self.data[col_ix].get(row_ix) {
+            Ok(AnyValue::String(str)) => Some(SharedString::new(str)),
+            Ok(AnyValue::StringOwned(str)) => Some(SharedString::new(str.as_str())),
+            Ok(AnyValue::Null) => None,
+            Ok(val) => Some(SharedString::new(
+                nested::nested_label(&val).unwrap_or_else(|| val.to_string()),
+            )),
+            Err(_) => Some(SharedString::new("ERR")),
+        }
+    }
+
+    /// Highlight the occurrences of the search text, the current match is highlighted more prominently
+    fn render_cell_text(
+        &self,
+        text: SharedString,
+        is_current_match: bool,
+        cx: &Context<TableState<Self>>,
+    ) -> Div {
+        let ranges = search::match_ranges(&text, &self.view_state.search);
+        if ranges.is_empty() {
+            return div().child(text);
+        }
+
+        let color = if is_current_match {
+            cx.theme().warning
+        } else {
+            cx.theme().warning.opacity(0.4)
+        };
+        let highlight = HighlightStyle {
+            background_color: Some(color),
+            ..Default::default()
+        };
+
+        div().child(
+            StyledText::new(text)
+                .with_highlights(ranges.into_iter().map(|range| (range, highlight))),
+        )
+    }
+
+    /// Recalculate the visible data from the original data using the current view state
+    fn update_view(&mut self, debounce: Duration, cx: &mut Context<TableState<Self>>) {
+        if self.view_state.is_empty() {
+            self.view_task = None;
+            self.sorting = false;
+            self.updating = false;
+            self.selection = None;
             self.data = self.original_data.clone();
+            self.data_version += 1;
+            cx.notify();
             return;
         }
 
         // Clone the data to move into background task
         let data = self.original_data.clone();
+        let view_state = self.view_state.clone();
+        self.updating = true;
 
-        // Spawn background task to perform filtering
-        cx.spawn(async move |table_state, cx| {
-            let filtered_data = cx
-                .background_executor()
-                .spawn(async move {
-                    let mut lazy_df = data.lazy();
-
-                    // Apply each filter using polars lazy API
-                    for (col_name, filter_text) in filters {
-                        // Create filter expression: cast to string, convert to lowercase, check if contains filter text
-                        let filter_expr = col(&col_name)
-                            .cast(polars::prelude::DataType::String)
-                            .str()
-                            .to_lowercase()
-                            .str()
-                            .contains(
-                                lit(filter_text.to_lowercase()),
-                                true, /* literal, use false for regex support*/
-                            );
-
-                        lazy_df = lazy_df.filter(filter_expr);
-                    }
+        // Replacing the task drops the previous one, which cancels it
+        // so the result of a superseded view can never overwrite a newer one
+        self.view_task = Some(cx.spawn(async move |table_state, cx| {
+            // Debounce the input, changes within the interval cancel this task
+            if !debounce.is_zero() {
+                cx.background_executor().timer(debounce).await;
+            }
 
-                    lazy_df.collect().ok()
-                })
+            let view_data = cx
+                .background_executor()
+                .spawn(async move { view_state.apply(data) })
                 .await;
 
             // Update the data on the UI thread
-            if let Some(filtered) = filtered_data {
-                let _ = table_state.update(cx, |table_state, cx| {
-                    table_state.delegate_mut().data = filtered;
-                    cx.notify();
-                });
+            let result = table_state.update(cx, |table_state, cx| {
+                let delegate = table_state.delegate_mut();
+                delegate.sorting = false;
+                delegate.updating = false;
+                cx.notify();
+                view_data.map(|view_data| {
+                    // The selected rows no longer correspond to the same data
+                    delegate.selection = None;
+                    delegate.data = view_data;
+                    delegate.data_version += 1;
+                })
+            });
+
+            if let Ok(Err(err)) = result {
+                utils::error_notification("Failed to update the table view", err.into(), cx);
             }
-        })
-        .detach();
+        }));
     }
 
     fn on_filter_input_event(
         &mut self,
-        _state: &Entity<InputState>,
+        state: &Entity<InputState>,
         event: &InputEvent,
         cx: &mut Context<TableState<Self>>,
     ) {
         match event {
             InputEvent::Change => {
-                self.filter_data(cx);
+                if let Some(col_ix) = self.filter_inputs.iter().position(|input| input == state) {
+                    let text = state.read(cx).value();
+                    self.view_state
+                        .set_filter(self.columns[col_ix].key.as_ref(), text.as_ref());
+                }
+                self.update_view(FILTER_DEBOUNCE, cx);
             }
             _ => {}
         };
     }
 
+    fn update_filter_options(
+        &mut self,
+        col_ix: usize,
+        update: fn(&mut FilterOptions),
+        cx: &mut Context<TableState<Self>>,
+    ) {
+        let column = self.columns[col_ix].key.to_string();
+        let mut options = self
+            .view_state
+            .filter(&column)
+            .map(ColumnFilter::options)
+            .unwrap_or_default();
+        update(&mut options);
+
+        self.view_state.set_filter_options(&column, options);
+        self.update_view(Duration::ZERO, cx);
+        cx.notify();
+    }
+
+    fn render_filter_options(
+        &self,
+        col_ix: usize,
+        options: FilterOptions,
+        cx: &Context<TableState<Self>>,
+    ) -> impl IntoElement {
+        let toggle = |id: &'static str,
+                      label: &'static str,
+                      tooltip: &'static str,
+                      selected: bool,
+                      update: fn(&mut FilterOptions)| {
+            Button::new((id, col_ix))
+                .label(label)
+                .ghost()
+                .xsmall()
+                .selected(selected)
+                .tooltip(tooltip)
+                .on_click(cx.listener(move |table_state, _, _, cx| {
+                    table_state
+                        .delegate_mut()
+                        .update_filter_options(col_ix, update, cx);
+                }))
+        };
+
+        h_flex()
+            .child(toggle(
+                "filter-case",
+                "Aa",
+                "Match case",
+                options.case_sensitive,
+                |options| options.case_sensitive = !options.case_sensitive,
+            ))
+            .child(toggle(
+                "filter-whole",
+                "W",
+                "Match whole value",
+                options.whole_value,
+                |options| options.whole_value = !options.whole_value,
+            ))
+            .child(toggle(
+                "filter-regex",
+                ".*",
+                "Use regular expression",
+                options.regex,
+                |options| options.regex = !options.regex,
+            ))
+    }
+
+    /// Show the distinct values of the column, they are counted on the background executor
+    fn open_value_checklist(
+        &mut self,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<TableState<Self>>,
+    ) {
+        let column = self.columns[col_ix].key.to_string();
+        let data = self.original_data.clone();
+
+        let search_input = cx.new(|cx| InputState::new(window, cx).placeholder("Search values"));
+        let subscription = cx.subscribe(&search_input, |_, _, _: &InputEvent, cx| cx.notify());
+
+        // Closing the checklist drops the task, which cancels the counting
+        let task = cx.spawn(async move |table_state, cx| {
+            let values =
+                cx.background_executor()
+                    .spawn(async move {
+                        valuefilter::distinct_values(&data, &column, MAX_DISTINCT_VALUES)
+                    })
+                    .await;
+
+            let result = table_state.update(cx, |table_state, cx| {
+                cx.notify();
+                let delegate = table_state.delegate_mut();
+                match values {
+                    Ok(values) => {
+                        if let Some(checklist) = &mut delegate.value_checklist {
+                            checklist.values = Some(values);
+                        }
+                        Ok(())
+                    }
+                    Err(err) => {
+                        delegate.value_checklist = None;
+                        Err(err)
+                    }
+                }
+            });
+
+            if let Ok(Err(err)) = result {
+                utils::error_notification("Failed to count the column values", err.into(), cx);
+            }
+        });
+
+        self.value_checklist = Some(ValueChecklist {
+            col_ix,
+            values: None,
+            search_input,
+            _subscription: subscription,
+            _task: task,
+        });
+        cx.notify();
+    }
+
+    fn close_value_checklist(&mut self, cx: &mut Context<TableState<Self>>) {
+        self.value_checklist = None;
+        cx.notify();
+    }
+
+    /// Select or deselect values of the open value checklist
+    fn set_values_selected(
+        &mut self,
+        values: &[Option<String>],
+        selected: bool,
+        cx: &mut Context<TableState<Self>>,
+    ) {
+        let Some(col_ix) = self
+            .value_checklist
+            .as_ref()
+            .map(|checklist| checklist.col_ix)
+        else {
+            return;
+        };
+
+        let column = self.columns[col_ix].key.to_string();
+        let mut filter = self
+            .view_state
+            .value_filter(&column)
+            .cloned()
+            .unwrap_or_else(|| ValueFilter::new(&column));
+        for value in values {
+            filter.set_selected(value.as_deref(), selected);
+        }
+
+        // Selecting all the values again is the same as not filtering at all
+        self.view_state
+            .set_value_filter(&column, (!filter.is_empty()).then_some(filter));
+        self.update_view(Duration::ZERO, cx);
+    }
+
+    fn render_value_checklist(
+        &self,
+        checklist: &ValueChecklist,
+        cx: &Context<TableState<Self>>,
+    ) -> impl IntoElement {
+        let column = self.columns[checklist.col_ix].key.as_ref();
+        let filter = self.view_state.value_filter(column);
+        let is_selected = |value: Option<&str>| filter.is_none_or(|filter| filter.contains(value));
+
+        let body = match &checklist.values {
+            None => div()
+                .text_xs()
+                .text_color(cx.theme().muted_foreground)
+                .child("Counting values…")
+                .into_any_element(),
+            Some(distinct) => {
+                let search = checklist.search_input.read(cx).value().to_lowercase();
+                let visible: Vec<_> = distinct
+                    .values
+                    .iter()
+                    .filter(|value| {
+                        value
+                            .value
+                            .as_deref()
+                            .unwrap_or(NULL_VALUE_LABEL)
+                            .to_lowercase()
+                            .contains(&search)
+                    })
+                    .collect();
+                let visible_values: Vec<Option<String>> =
+                    visible.iter().map(|value| value.value.clone()).collect();
+                let all_selected = visible_values
+                    .iter()
+                    .all(|value| is_selected(value.as_deref()));
+
+                v_flex()
+                    .gap_1()
+                    .child(
+                        // Only the values that match the search text are (de)selected
+                        Checkbox::new("value-checklist-all")
+                            .label("(Select all)")
+                            .checked(all_selected)
+                            .on_click(cx.listener(move |table_state, checked: &bool, _, cx| {
+                                table_state.delegate_mut().set_values_selected(
+                                    &visible_values,
+                                    *checked,
+                                    cx,
+                                );
+                            })),
+                    )
+                    .child(
+                        v_flex()
+                            .id("value-checklist-values")
+                            .gap_1()
+                            .max_h(px(300.))
+                            .overflow_y_scroll()
+                            .children(visible.into_iter().enumerate().map(|(ix, value)| {
+                                let label = format!(
+                                    "{} ({})",
+                                    value.value.as_deref().unwrap_or(NULL_VALUE_LABEL),
+                                    value.count
+                                );
+                                let checked = is_selected(value.value.as_deref());
+                                let value = [value.value.clone()];
+
+                                Checkbox::new(("value-checklist-value", ix))
+                                    .label(label)
+                                    .checked(checked)
+                                    .on_click(cx.listener(
+                                        move |table_state, checked: &bool, _, cx| {
+                                            table_state
+                                                .delegate_mut()
+                                                .set_values_selected(&value, *checked, cx);
+                                        },
+                                    ))
+                            })),
+                    )
+                    .when(distinct.truncated, |this| {
+                        this.child(
+                            div()
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground)
+                                .child(format!(
+                                    "Only the {MAX_DISTINCT_VALUES} most frequent values are listed"
+                                )),
+                        )
+                    })
+                    .into_any_element()
+            }
+        };
+
+        deferred(
+            anchored().snap_to_window_with_margin(px(8.)).child(
+                v_flex()
+                    .occlude()
+                    .w(px(260.))
+                    .p_2()
+                    .gap_2()
+                    .bg(cx.theme().popover)
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded(cx.theme().radius)
+                    .shadow_lg()
+                    .on_mouse_down_out(cx.listener(|table_state, _, _, cx| {
+                        table_state.delegate_mut().close_value_checklist(cx);
+                    }))
+                    .child(
+                        Input::new(&checklist.search_input)
+                            .prefix(Icon::new(IconName::Search))
+                            .xsmall(),
+                    )
+                    .child(body),
+            ),
+        )
+    }
+
+    /// Show the sort direction of the primary sort key in the column headers
+    fn update_column_sort(&mut self) {
+        let primary_key = self.view_state.sort.first();
+        for column in &mut self.columns {
+            let sort = match primary_key {
+                Some(key) if key.column == column.key.as_ref() && key.descending => {
+                    ColumnSort::Descending
+                }
+                Some(key) if key.column == column.key.as_ref() => ColumnSort::Ascending,
+                _ => ColumnSort::Default,
+            };
+            column.sort = Some(sort);
+        }
+    }
+
     fn create_column_info(&mut self) {
         let schema = self.data.schema();
         self.columns = schema
@@ -133,12 +798,13 @@ impl TableLayer {
 
         self.input_subscriptions.clear();
         self.filter_inputs.clear();
+        self.view_state = ViewState::new(self.data.schema().clone());
     }
 }
 
 impl TableDelegate for TableLayer {
     fn loading(&self, _cx: &App) -> bool {
-        self.columns.is_empty()
+        self.columns.is_empty() || self.sorting
     }
 
     fn columns_count(&self, _: &App) -> usize {
@@ -162,8 +828,17 @@ impl TableDelegate for TableLayer {
         let mut div = div().id("header");
         if self.filter_enabled {
             if self.filter_inputs.is_empty() {
-                for _ in 0..self.columns.len() {
-                    let input = cx.new(|cx| InputState::new(window, cx).clean_on_escape());
+                for column in &self.columns {
+                    let filter_text = self
+                        .view_state
+                        .filter(column.key.as_ref())
+                        .map(|filter| filter.text().to_string())
+                        .unwrap_or_default();
+                    let input = cx.new(|cx| {
+                        InputState::new(window, cx)
+                            .default_value(filter_text)
+                            .clean_on_escape()
+                    });
                     self.input_subscriptions.push(cx.subscribe(
                         &input,
                         |this, entity, event: &InputEvent, cx| {
@@ -187,18 +862,105 @@ impl TableDelegate for TableLayer {
         _window: &mut Window,
         cx: &mut Context<TableState<Self>>,
     ) -> impl IntoElement {
-        let mut div = div()
-            .v_flex()
-            .size_full()
-            .child(self.column(col_ix, cx).name.clone());
+        let column = self.column(col_ix, cx);
+        let mut title = h_flex().gap_1().child(column.name.clone());
+
+        let is_struct = self
+            .original_data
+            .schema()
+            .get(column.key.as_ref())
+            .is_some_and(|dtype| matches!(dtype, DataType::Struct(_)));
+        if is_struct {
+            title = title.child(
+                Button::new(("expand-struct", col_ix))
+                    .icon(IconName::Plus)
+                    .ghost()
+                    .xsmall()
+                    .tooltip("Expand into a column per field")
+                    .on_click(cx.listener(move |table, _, window, cx| {
+                        cx.stop_propagation();
+                        table
+                            .delegate_mut()
+                            .expand_struct_column(col_ix, window, cx);
+                        table.refresh(cx);
+                    })),
+            );
+        }
+
+        // The built-in sort indicator only shows a single column, show the priority of every sort key
+        if self.view_state.sort.len() > 1
+            && let Some((priority, descending)) = self.view_state.sort_priority(column.key.as_ref())
+        {
+            let icon = if descending {
+                IconName::ArrowDown
+            } else {
+                IconName::ArrowUp
+            };
+
+            title = title.child(
+                h_flex()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(Icon::new(icon).xsmall())
+                    .child(priority.to_string()),
+            );
+        }
+
+        let mut div = div().v_flex().size_full().child(title);
 
         if self.filter_enabled {
+            let filter = self.view_state.filter(column.key.as_ref());
+            let options = filter.map(ColumnFilter::options).unwrap_or_default();
+            let error = filter
+                .and_then(ColumnFilter::error)
+                .map(|err| SharedString::from(err.to_string()));
+
+            let has_value_filter = self.view_state.value_filter(column.key.as_ref()).is_some();
+            let input = Input::new(&self.filter_inputs.get(col_ix).expect("BUG: column index"))
+                .prefix(Icon::new(IconName::Search))
+                .suffix(self.render_filter_options(col_ix, options, cx))
+                .text_xs()
+                .xsmall()
+                .when(error.is_some(), |input| {
+                    input.border_color(cx.theme().danger)
+                });
+
             div = div.child(
-                Input::new(&self.filter_inputs.get(col_ix).expect("BUG: column index"))
-                    .prefix(Icon::new(IconName::Search))
-                    .text_xs()
-                    .xsmall(),
+                h_flex()
+                    .gap_1()
+                    .child(
+                        gpui::div()
+                            .id(("filter", col_ix))
+                            .flex_1()
+                            .child(input)
+                            .when_some(error, |this, error| {
+                                this.tooltip(move |window, cx| {
+                                    Tooltip::new(error.clone()).build(window, cx)
+                                })
+                            }),
+                    )
+                    .child(
+                        Button::new(("value-checklist", col_ix))
+                            .icon(IconName::ChevronDown)
+                            .ghost()
+                            .xsmall()
+                            .selected(has_value_filter)
+                            .tooltip("Filter by value")
+                            .on_click(cx.listener(move |table_state, _, window, cx| {
+                                table_state
+                                    .delegate_mut()
+                                    .open_value_checklist(col_ix, window, cx);
+                            })),
+                    ),
             );
+
+            if let Some(checklist) = self
+                .value_checklist
+                .as_ref()
+                .filter(|checklist| checklist.col_ix == col_ix)
+            {
+                div = div.child(self.render_value_checklist(checklist, cx));
+            }
         }
 
         div
@@ -211,49 +973,147 @@ impl TableDelegate for TableLayer {
         _: &mut Window,
         cx: &mut gpui::Context<'_, TableState<Self>>,
     ) -> impl IntoElement {
-        match self.data[col_ix].get(row_ix) {
-            Ok(AnyValue::String(str)) => div().child(SharedString::new(str)),
-            Ok(AnyValue::StringOwned(str)) => div().child(SharedString::new(str.as_str())),
-            Ok(AnyValue::Null) => div()
+        let cell = match self.cell_text(row_ix, col_ix) {
+            Some(text) => {
+                let is_current_match = self.current_match == Some((row_ix, col_ix));
+                self.render_cell_text(text, is_current_match, cx)
+            }
+            None => div()
                 .flex()
                 .justify_center()
                 .child(Tag::secondary().outline().xsmall().child(NULL))
                 .text_color(cx.theme().accent),
-            Ok(val) => div().child(SharedString::new(val.to_string())),
-            Err(_) => div().child(SharedString::new("ERR")),
+        };
+
+        let selected = self
+            .selection
+            .is_some_and(|selection| selection.contains(row_ix, col_ix));
+
+        let is_list = matches!(self.data[col_ix].dtype(), DataType::List(_))
+            && !matches!(self.data[col_ix].get(row_ix), Ok(AnyValue::Null));
+        let cell = if is_list {
+            h_flex().justify_between().child(cell).child(
+                Button::new(ElementId::Name(
+                    format!("drill-down-{row_ix}-{col_ix}").into(),
+                ))
+                .icon(IconName::ChevronRight)
+                .ghost()
+                .xsmall()
+                .tooltip("Show the items")
+                .on_click(move |_, window, cx| {
+                    window.dispatch_action(Box::new(DrillDown { row_ix, col_ix }), cx);
+                }),
+            )
+        } else {
+            cell
+        };
+
+        cell.size_full()
+            .when(selected, |this| this.bg(cx.theme().table_active))
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |table, event: &MouseDownEvent, _, cx| {
+                    table
+                        .delegate_mut()
+                        .select_cell(row_ix, col_ix, event.modifiers.shift);
+                    cx.notify();
+                }),
+            )
+            // Dragging over the cells extends the selection
+            .on_mouse_move(cx.listener(move |table, event: &MouseMoveEvent, _, cx| {
+                if event.dragging() && table.delegate_mut().extend_selection(row_ix, col_ix) {
+                    cx.notify();
+                }
+            }))
+    }
+
+    fn context_menu(
+        &mut self,
+        row_ix: usize,
+        menu: PopupMenu,
+        _window: &mut Window,
+        _cx: &mut Context<TableState<Self>>,
+    ) -> PopupMenu {
+        // Copy the clicked row when it is not part of the selection
+        if !self
+            .selection
+            .is_some_and(|selection| selection.rows().contains(&row_ix))
+        {
+            self.selection = Some(Selection::cell(row_ix, 0).full_rows(self.columns.len()));
         }
+
+        let mut menu = menu.menu("Copy", Box::new(CopySelection)).separator();
+        for (index, format) in CopyFormat::ALL.iter().enumerate() {
+            menu = menu.menu(
+                format!("Copy as {}", format.name()),
+                Box::new(CopySelectionAs { index }),
+            );
+        }
+        menu
     }
 
     fn perform_sort(
         &mut self,
         col_ix: usize,
-        sort: ColumnSort,
-        _: &mut Window,
-        _: &mut Context<TableState<Self>>,
+        _sort: ColumnSort,
+        window: &mut Window,
+        cx: &mut Context<TableState<Self>>,
     ) {
-        let col = &self.columns[col_ix];
+        // The table resets the sort of the other columns on every click, so the direction of
+        // a secondary sort key is derived from the view state instead of the column
+        let column = self.columns[col_ix].key.clone();
+        // Shift-click adds the column as an additional sort key
+        let append = window.modifiers().shift;
+        let descending = self.view_state.next_sort_direction(column.as_ref(), append);
+        self.view_state
+            .set_sort(column.as_ref(), descending, append);
+        self.update_column_sort();
 
-        let mut temp_df = DataFrame::default();
-        std::mem::swap(&mut self.data, &mut temp_df);
+        // Sorting large tables takes a while, show the loading state until the sorted data is available
+        self.sorting = true;
+        self.update_view(Duration::ZERO, cx);
+    }
 
-        let df = temp_df.lazy();
-        let sort_options = match sort {
-            ColumnSort::Ascending => polars::prelude::SortMultipleOptions::default(),
-            ColumnSort::Descending => {
-                polars::prelude::SortMultipleOptions::default().with_order_descending(true)
-            }
-            ColumnSort::Default => {
-                // No sorting, return original DataFrame
-                self.data = df.collect().unwrap();
-                return;
-            }
+    /// Move a dragged column, the data is reordered as well so the column indices of the
+    /// filter inputs, the selection and the exported data keep matching the table
+    fn move_column(
+        &mut self,
+        col_ix: usize,
+        to_ix: usize,
+        _window: &mut Window,
+        cx: &mut Context<TableState<Self>>,
+    ) {
+        if col_ix == to_ix || col_ix >= self.columns.len() || to_ix >= self.columns.len() {
+            return;
         }
-        .with_multithreaded(true)
-        .with_nulls_last(true);
 
-        self.data = df
-            .sort(vec![PlSmallStr::from(col.key.as_ref())], sort_options)
-            .collect()
-            .unwrap();
+        let mut columns = self.columns.clone();
+        let column = columns.remove(col_ix);
+        columns.insert(to_ix, column);
+        let names = columns.iter().map(|column| column.key.as_ref());
+        let (Ok(original_data), Ok(data)) = (
+            self.original_data.select(names.clone()),
+            self.data.select(names),
+        ) else {
+            return;
+        };
+
+        self.columns = columns;
+        if !self.filter_inputs.is_empty() {
+            let input = self.filter_inputs.remove(col_ix);
+            self.filter_inputs.insert(to_ix, input);
+        }
+        self.original_data = original_data;
+        self.data = data;
+        self.data_version += 1;
+        self.selection = None;
+        self.current_match = None;
+        self.value_checklist = None;
+
+        // A view that is still being calculated has the previous column order
+        if self.updating {
+            self.update_view(Duration::ZERO, cx);
+        }
+        cx.notify();
     }
 }
@@ -1,21 +1,100 @@
+use gpui::prelude::FluentBuilder as _;
 use gpui::*;
 use gpui::{App, IntoElement, Window};
+use gpui_component::button::{Button, ButtonVariants as _};
+use gpui_component::input::{Input, InputEvent, InputState};
 use gpui_component::kbd::Kbd;
+use gpui_component::menu::DropdownMenu as _;
 use gpui_component::notification::Notification;
+use gpui_component::spinner::Spinner;
 use gpui_component::tab::{Tab, TabBar};
-use gpui_component::table::{Table, TableState};
+use gpui_component::table::{Table, TableDelegate as _, TableEvent, TableState};
 use gpui_component::*;
-use std::path::PathBuf;
+use polars::frame::DataFrame;
+use polars::prelude::{IntoLazy, LazyFrame};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-use crate::tablelayer::TableLayer;
-use crate::tabulite::ToggleFilter;
-use crate::{tableio, utils};
+use crate::RECENT_FILES_CONTEXT;
+use crate::columnstats::{self, ColumnStats};
+use crate::csvdialog::{CsvDialogEvent, CsvOptionsDialog};
+use crate::csvsettings::CsvSettings;
+use crate::filterpresets::{FilterPreset, FilterPresets};
+use crate::layercache::LayerCache;
+use crate::queryhistory::QueryHistory;
+use crate::recentfiles::RecentFiles;
+use crate::selection::{self, CopyFormat};
+use crate::tableio::{LayerData, ReadOptions};
+use crate::tablelayer::{LayerSnapshot, TableLayer};
+use crate::tablewriter::{self, ExportFormat};
+use crate::tabulite::{
+    ApplyPreset, CopySelection, CopySelectionAs, DrillDown, ExportAs, Open, OpenDirectory,
+    OpenRecent, ProfileDataset, ReopenWithOptions, SavePreset, SelectColumns, SelectQuery,
+    SelectRows, ToggleFilter, ToggleQueryPanel, ToggleSearch,
+};
+use crate::textencoding::TextEncoding;
+use crate::{profile, sqlquery, tableio, utils};
+
+/// The result of a SQL query or the items of a list cell, shown in a tab after the layers of the file
+struct QueryResult {
+    name: SharedString,
+    snapshot: LayerSnapshot,
+}
+
+/// The statistics of the selected column, shown in a side panel
+struct ColumnStatsPanel {
+    col_ix: usize,
+    column: SharedString,
+    /// The version of the visible data the statistics are calculated from
+    data_version: usize,
+    /// `None` while the statistics are being calculated
+    stats: Option<ColumnStats>,
+    _task: Task<()>,
+}
+
+/// The read options of the delimited text file, shown above the table
+struct CsvDialogPanel {
+    dialog: Entity<CsvOptionsDialog>,
+    _subscription: Subscription,
+}
 
 pub struct TableView {
+    focus_handle: FocusHandle,
     active_tab: usize,
     data_path: Option<PathBuf>,
+    /// The canonical path of the loaded file, used to match the filter presets
+    canonical_path: Option<PathBuf>,
+    /// The size of the loaded file in bytes, or of the parquet files of a loaded directory
+    file_size: Option<u64>,
+    /// The character encoding of a delimited text file, detected or chosen by the user
+    text_encoding: Option<TextEncoding>,
+    /// The time it took to read the active layer from disk, `None` when it was restored from the cache
+    load_time: Option<Duration>,
+    /// The number of files or layers that are being read on the background executor
+    pending_loads: usize,
     layer_names: Vec<SharedString>,
+    /// The layer whose data the table holds, `None` while it is loading or a query result is shown
+    table_layer: Option<SharedString>,
+    /// The layers of which only the first rows were read because of the database row limit
+    truncated_layers: HashSet<SharedString>,
     table: Entity<TableState<TableLayer>>,
+    search_visible: bool,
+    search_input: Entity<InputState>,
+    query_visible: bool,
+    query_input: Entity<InputState>,
+    query_error: Option<SharedString>,
+    query_task: Option<Task<()>>,
+    copy_task: Option<Task<()>>,
+    query_results: Vec<QueryResult>,
+    /// Number of queries executed on the current file, used to name the result tabs
+    query_count: usize,
+    preset_editor_visible: bool,
+    preset_name_input: Entity<InputState>,
+    preset_file_input: Entity<InputState>,
+    column_stats: Option<ColumnStatsPanel>,
+    csv_dialog: Option<CsvDialogPanel>,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl TableView {
@@ -25,20 +104,249 @@ impl TableView {
 
     fn new(path: Option<PathBuf>, window: &mut Window, cx: &mut gpui::Context<Self>) -> Self {
         let table = cx.new(|cx| TableState::new(TableLayer::default(), window, cx));
+        let focus_handle = cx.focus_handle();
+        window.focus(&focus_handle);
+
+        let search_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Search all columns")
+                .clean_on_escape()
+        });
+        let query_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .placeholder("SELECT * FROM Sheet1")
+        });
+        let preset_name_input = cx.new(|cx| InputState::new(window, cx).placeholder("Preset name"));
+        let preset_file_input = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("File path or pattern, e.g. export_*.csv")
+        });
+        let _subscriptions = vec![
+            // Keep the status bar and the column statistics in sync with the table
+            cx.observe(&table, |this, _, cx| {
+                this.refresh_column_stats(cx);
+                cx.notify();
+            }),
+            cx.subscribe_in(&table, window, Self::on_table_event),
+            cx.subscribe_in(&search_input, window, Self::on_search_input_event),
+            cx.subscribe_in(&query_input, window, Self::on_query_input_event),
+            cx.subscribe_in(&preset_name_input, window, Self::on_preset_input_event),
+            cx.subscribe_in(&preset_file_input, window, Self::on_preset_input_event),
+        ];
 
         if let Some(path) = path {
-            Self::load_table(path, cx).detach();
+            Self::load_table(path, None, cx).detach();
         }
 
         Self {
+            focus_handle,
             active_tab: 0,
             data_path: None,
+            canonical_path: None,
+            file_size: None,
+            text_encoding: None,
+            load_time: None,
+            pending_loads: 0,
             table,
             layer_names: Vec::default(),
+            table_layer: None,
+            truncated_layers: HashSet::new(),
+            search_visible: false,
+            search_input,
+            query_visible: false,
+            query_input,
+            query_error: None,
+            query_task: None,
+            copy_task: None,
+            query_results: Vec::new(),
+            query_count: 0,
+            preset_editor_visible: false,
+            preset_name_input,
+            preset_file_input,
+            column_stats: None,
+            csv_dialog: None,
+            _subscriptions,
         }
     }
 
-    fn on_action_toggle_search(
+    /// The tab title of the document, the name of the loaded file
+    pub fn title(&self) -> SharedString {
+        self.data_path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().to_string().into())
+            .unwrap_or_else(|| "Untitled".into())
+    }
+
+    pub fn focus(&self, window: &mut Window) {
+        window.focus(&self.focus_handle);
+    }
+
+    fn on_table_event(
+        &mut self,
+        _: &Entity<TableState<TableLayer>>,
+        event: &TableEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let TableEvent::SelectColumn(col_ix) = event {
+            self.table.update(cx, |table, cx| {
+                table.delegate_mut().select_column(*col_ix);
+                cx.notify();
+            });
+            self.show_column_stats(*col_ix, cx);
+        }
+    }
+
+    /// Calculate the statistics of the visible values of the column on the background executor
+    fn show_column_stats(&mut self, col_ix: usize, cx: &mut Context<Self>) {
+        let layer = self.table.read(cx).delegate();
+        let Some(series) = layer.column_series(col_ix) else {
+            return;
+        };
+        let data_version = layer.data_version();
+
+        let column = SharedString::from(series.name().to_string());
+        let task = cx.spawn(async move |this, cx| {
+            let stats = cx
+                .background_executor()
+                .spawn(async move { columnstats::column_stats(&series) })
+                .await;
+
+            match stats {
+                Ok(stats) => {
+                    let _ = this.update(cx, |this, cx| {
+                        if let Some(panel) = &mut this.column_stats {
+                            panel.stats = Some(stats);
+                            cx.notify();
+                        }
+                    });
+                }
+                Err(err) => {
+                    let _ = this.update(cx, |this, cx| this.close_column_stats(cx));
+                    utils::error_notification(
+                        "Failed to calculate the column statistics",
+                        err.into(),
+                        cx,
+                    );
+                }
+            }
+        });
+
+        self.column_stats = Some(ColumnStatsPanel {
+            col_ix,
+            column,
+            data_version,
+            stats: None,
+            _task: task,
+        });
+        cx.notify();
+    }
+
+    /// Recalculate the statistics when the visible data changed, e.g. after filtering
+    fn refresh_column_stats(&mut self, cx: &mut Context<Self>) {
+        let data_version = self.table.read(cx).delegate().data_version();
+        if let Some(panel) = &self.column_stats
+            && panel.data_version != data_version
+        {
+            self.show_column_stats(panel.col_ix, cx);
+        }
+    }
+
+    fn close_column_stats(&mut self, cx: &mut Context<Self>) {
+        self.column_stats = None;
+        cx.notify();
+    }
+
+    /// Show the items of a list cell in a result tab
+    fn on_action_drill_down(
+        &mut self,
+        action: &DrillDown,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let layer = self.table.read(cx).delegate();
+        let name = layer.column_name(action.col_ix);
+        match layer.list_items(action.row_ix, action.col_ix) {
+            Ok(Some(items)) => {
+                let name = format!("{} #{}", name.unwrap_or_default(), action.row_ix + 1);
+                self.add_query_result(name.into(), items, cx);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                let message = utils::error_message("Failed to show the list items", err.into());
+                window.push_notification(Notification::error(message), cx);
+            }
+        }
+    }
+
+    fn on_action_copy_selection(
+        &mut self,
+        _: &CopySelection,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.copy_selection(CopyFormat::Tsv, cx);
+    }
+
+    fn on_action_copy_selection_as(
+        &mut self,
+        action: &CopySelectionAs,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(&format) = CopyFormat::ALL.get(action.index) {
+            self.copy_selection(format, cx);
+        }
+    }
+
+    /// Format the selected cells on the background executor, a selection can span the whole table
+    /// Replacing the task cancels a copy that is still being formatted
+    fn copy_selection(&mut self, format: CopyFormat, cx: &mut Context<Self>) {
+        let layer = self.table.read(cx).delegate();
+        let Some(selection) = layer.selection() else {
+            return;
+        };
+        let data = layer.visible_data().clone();
+
+        self.copy_task = Some(cx.spawn(async move |this, cx| {
+            let text = cx
+                .background_executor()
+                .spawn(async move { selection::format_selection(&data, &selection, format) })
+                .await;
+
+            let _ = this.update(cx, |this, cx| {
+                this.copy_task = None;
+                cx.write_to_clipboard(ClipboardItem::new_string(text));
+            });
+        }));
+    }
+
+    fn on_action_select_rows(
+        &mut self,
+        _: &SelectRows,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.table.update(cx, |table, cx| {
+            table.delegate_mut().select_rows();
+            cx.notify();
+        });
+    }
+
+    fn on_action_select_columns(
+        &mut self,
+        _: &SelectColumns,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.table.update(cx, |table, cx| {
+            table.delegate_mut().select_columns();
+            cx.notify();
+        });
+    }
+
+    fn on_action_toggle_filter(
         &mut self,
         _: &ToggleFilter,
         _window: &mut Window,
@@ -53,26 +361,692 @@ impl TableView {
         cx.propagate();
     }
 
-    fn load_table(path: PathBuf, cx: &mut gpui::Context<Self>) -> Task<()> {
+    fn on_action_toggle_search(
+        &mut self,
+        _: &ToggleSearch,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.search_visible = !self.search_visible;
+        if self.search_visible {
+            self.search_input
+                .update(cx, |input, cx| input.focus(window, cx));
+        } else {
+            // Closing the search bar shows all the rows again
+            self.search_input
+                .update(cx, |input, cx| input.set_value("", window, cx));
+            self.table
+                .update(cx, |table, cx| table.delegate_mut().set_search("", cx));
+            self.focus(window);
+        }
+
+        cx.notify();
+    }
+
+    fn on_search_input_event(
+        &mut self,
+        state: &Entity<InputState>,
+        event: &InputEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            InputEvent::Change => {
+                let text = state.read(cx).value();
+                self.table
+                    .update(cx, |table, cx| table.delegate_mut().set_search(&text, cx));
+            }
+            // Enter moves to the next match, shift-enter to the previous one
+            InputEvent::PressEnter { .. } => {
+                self.select_next_match(!window.modifiers().shift, cx);
+            }
+            _ => {}
+        }
+    }
+
+    /// The search text that applies to every layer of the document
+    fn search_text(&self, cx: &App) -> String {
+        if self.search_visible {
+            self.search_input.read(cx).value().to_string()
+        } else {
+            String::new()
+        }
+    }
+
+    /// Scroll the table to the next (or previous) cell that matches the search text
+    fn select_next_match(&mut self, forward: bool, cx: &mut Context<Self>) {
+        self.table.update(cx, |table, cx| {
+            if let Some(row_ix) = table.delegate_mut().select_next_match(forward) {
+                table.set_selected_row(row_ix, cx);
+            }
+            cx.notify();
+        });
+    }
+
+    fn on_action_toggle_query_panel(
+        &mut self,
+        _: &ToggleQueryPanel,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.query_visible = !self.query_visible;
+        if self.query_visible {
+            self.query_input
+                .update(cx, |input, cx| input.focus(window, cx));
+        } else {
+            self.focus(window);
+        }
+
+        cx.notify();
+    }
+
+    fn on_query_input_event(
+        &mut self,
+        _state: &Entity<InputState>,
+        event: &InputEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        // Enter adds a new line, the secondary modifier (cmd/ctrl-enter) runs the query
+        if let InputEvent::PressEnter { secondary: true } = event {
+            self.run_query(cx);
+        }
+    }
+
+    fn on_action_select_query(
+        &mut self,
+        action: &SelectQuery,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(query) = cx
+            .global::<QueryHistory>()
+            .queries()
+            .get(action.index)
+            .cloned()
+        {
+            self.query_input
+                .update(cx, |input, cx| input.set_value(query, window, cx));
+        }
+    }
+
+    /// Run the SQL query of the query panel, every layer of the file is available as a table
+    /// The result is shown in a new tab
+    fn run_query(&mut self, cx: &mut Context<Self>) {
+        let query = self.query_input.read(cx).value().trim().to_string();
+        let Some(path) = self.data_path.clone() else {
+            return;
+        };
+        if query.is_empty() {
+            return;
+        }
+
+        let history = cx.global_mut::<QueryHistory>();
+        history.add(&query);
+        history.save();
+
+        // Make sure the active layer is present in the cache
+        self.store_layer_state(cx);
+        let document = cx.entity_id();
+
+        // Layers that are not cached are read from disk, but only when the query uses them
+        // Cached layers that are truncated by the row limit are read again, a query uses all the rows
+        let layers: Vec<(String, Option<DataFrame>)> =
+            sqlquery::referenced_tables(&query, self.layer_names.iter().map(SharedString::as_str))
+                .into_iter()
+                .map(|layer| {
+                    let data = if self.truncated_layers.contains(layer) {
+                        None
+                    } else {
+                        cx.global_mut::<LayerCache>()
+                            .get(document, &path, layer)
+                            .map(|snapshot| snapshot.original_data().clone())
+                    };
+                    (layer.to_string(), data)
+                })
+                .collect();
+
+        // Earlier results can be queried as well
+        let mut tables: Vec<(String, LazyFrame)> = self
+            .query_results
+            .iter()
+            .map(|result| {
+                let data = result.snapshot.original_data().clone();
+                (result.name.to_string(), data.lazy())
+            })
+            .collect();
+
+        let options = Self::read_options(&path, cx);
+        self.query_error = None;
+        // Replacing the task cancels a query that is still running
+        self.query_task = Some(cx.spawn(async move |this, cx| {
+            let result: Result<DataFrame> = cx
+                .background_executor()
+                .spawn(async move {
+                    for (layer, data) in layers {
+                        let data = match data {
+                            Some(data) => data.lazy(),
+                            None => tableio::scan_layer(&path, &layer, &options)?,
+                        };
+                        tables.push((layer, data));
+                    }
+
+                    Ok(sqlquery::run_query(tables, &query)?)
+                })
+                .await;
+
+            let _ = this.update(cx, |this, cx| {
+                this.query_task = None;
+                match result {
+                    Ok(data) => {
+                        this.query_count += 1;
+                        let name = format!("Query {}", this.query_count);
+                        this.add_query_result(name.into(), data, cx);
+                    }
+                    Err(err) => this.query_error = Some(format!("{err:#}").into()),
+                }
+                cx.notify();
+            });
+        }));
+        cx.notify();
+    }
+
+    fn add_query_result(&mut self, name: SharedString, data: DataFrame, cx: &mut Context<Self>) {
+        self.store_layer_state(cx);
+
+        self.query_results.push(QueryResult {
+            name,
+            snapshot: LayerSnapshot::new(data),
+        });
+        self.show_tab(self.layer_names.len() + self.query_results.len() - 1, cx);
+    }
+
+    fn close_query_result(&mut self, index: usize, cx: &mut Context<Self>) {
+        let tab = self.layer_names.len() + index;
+        if index >= self.query_results.len() {
+            return;
+        }
+
+        self.query_results.remove(index);
+        if self.active_tab == tab {
+            // The first tab is replaced by the next result when there are no layers
+            self.show_tab(tab.saturating_sub(1), cx);
+            if self.layer_names.is_empty() && self.query_results.is_empty() {
+                self.table.update(cx, |table, cx| {
+                    table.delegate_mut().clear();
+                    table.refresh(cx);
+                });
+            }
+        } else if self.active_tab > tab {
+            self.active_tab -= 1;
+        }
+        cx.notify();
+    }
+
+    /// The query result that is shown in the given tab, `None` for the layer tabs
+    fn query_result_mut(&mut self, tab: usize) -> Option<&mut QueryResult> {
+        let index = tab.checked_sub(self.layer_names.len())?;
+        self.query_results.get_mut(index)
+    }
+
+    /// The name of the active layer, `None` when a query result is shown
+    fn active_layer(&self) -> Option<&SharedString> {
+        self.layer_names.get(self.active_tab)
+    }
+
+    fn on_action_apply_preset(
+        &mut self,
+        action: &ApplyPreset,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(preset) = cx
+            .global::<FilterPresets>()
+            .presets()
+            .get(action.index)
+            .cloned()
+        else {
+            return;
+        };
+
+        self.table.update(cx, |table, cx| {
+            table.sortable = true;
+            table.delegate_mut().apply_preset(&preset, window, cx);
+            table.refresh(cx);
+        });
+    }
+
+    fn on_action_save_preset(
+        &mut self,
+        _: &SavePreset,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(path) = self.canonical_path.clone() else {
+            return;
+        };
+
+        // Presets are stored for the file by default, the path can be replaced by a pattern
+        self.preset_file_input.update(cx, |input, cx| {
+            input.set_value(path.display().to_string(), window, cx)
+        });
+        self.preset_name_input
+            .update(cx, |input, cx| input.focus(window, cx));
+        self.preset_editor_visible = true;
+        cx.notify();
+    }
+
+    fn on_preset_input_event(
+        &mut self,
+        _state: &Entity<InputState>,
+        event: &InputEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::PressEnter { .. } = event {
+            self.save_preset(window, cx);
+        }
+    }
+
+    /// Store the filters and sort keys of the active layer as a preset
+    fn save_preset(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let name = self.preset_name_input.read(cx).value().trim().to_string();
+        let file = self.preset_file_input.read(cx).value().trim().to_string();
+        if name.is_empty() || file.is_empty() {
+            return;
+        }
+
+        let Some(layer) = self.active_layer().map(SharedString::to_string) else {
+            return;
+        };
+        // The layer of a single layer file is named after the file, which differs between matching files
+        let layer = (self.layer_names.len() > 1).then_some(layer);
+
+        let preset = FilterPreset::new(
+            &name,
+            &file,
+            layer.as_deref(),
+            self.table.read(cx).delegate().view_state(),
+        );
+        let presets = cx.global_mut::<FilterPresets>();
+        presets.add(preset);
+        presets.save();
+
+        self.close_preset_editor(window, cx);
+    }
+
+    fn close_preset_editor(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.preset_editor_visible = false;
+        self.preset_name_input
+            .update(cx, |input, cx| input.set_value("", window, cx));
+        self.focus(window);
+        cx.notify();
+    }
+
+    fn on_action_open(&mut self, _: &Open, _window: &mut Window, cx: &mut Context<Self>) {
+        Self::prompt_and_open(
+            PathPromptOptions {
+                files: true,
+                directories: false,
+                multiple: false,
+                prompt: Some("Open table".into()),
+            },
+            cx,
+        );
+    }
+
+    /// Open a directory of (hive partitioned) parquet files as a single table
+    /// Not every platform dialog can select files and directories at once, so it is a separate action
+    fn on_action_open_directory(
+        &mut self,
+        _: &OpenDirectory,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        Self::prompt_and_open(
+            PathPromptOptions {
+                files: false,
+                directories: true,
+                multiple: false,
+                prompt: Some("Open parquet directory".into()),
+            },
+            cx,
+        );
+    }
+
+    fn prompt_and_open(options: PathPromptOptions, cx: &mut Context<Self>) {
+        // The platform file dialogs offered by gpui have no file type filters,
+        // so the selection is validated against the supported formats instead
+        let paths = cx.prompt_for_paths(options);
+
+        cx.spawn(async move |this, cx| {
+            let path = match paths.await {
+                Ok(Ok(Some(paths))) => paths.into_iter().next(),
+                Ok(Ok(None)) | Err(_) => None,
+                Ok(Err(err)) => {
+                    utils::error_notification("Failed to show the file dialog", err, cx);
+                    None
+                }
+            };
+
+            let Some(path) = path else {
+                return;
+            };
+
+            Self::open_if_supported(&this, path, cx);
+        })
+        .detach();
+    }
+
+    /// Write the filtered and sorted data of the active tab to a file chosen by the user
+    fn on_action_export_as(
+        &mut self,
+        action: &ExportAs,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(&format) = ExportFormat::ALL.get(action.index) else {
+            return;
+        };
+        let Some(data_path) = &self.data_path else {
+            return;
+        };
+
+        let mut data = match self.table.read(cx).delegate().export_data() {
+            Ok(data) => data,
+            Err(err) => {
+                let message = utils::error_message("Failed to export the table", err.into());
+                window.push_notification(Notification::error(message), cx);
+                return;
+            }
+        };
+
+        let directory = data_path.parent().map(PathBuf::from).unwrap_or_default();
+        let stem = data_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let suggested_name = match self.query_result_mut(self.active_tab) {
+            Some(result) => format!("{stem}_{}", result.name),
+            None if self.layer_names.len() > 1 => {
+                format!("{stem}_{}", self.layer_names[self.active_tab])
+            }
+            None => stem,
+        };
+        let suggested_name = format!("{suggested_name}.{}", format.extension());
+        let export_path = cx.prompt_for_new_path(&directory, Some(&suggested_name));
+
+        cx.spawn(async move |this, cx| {
+            let export_path = match export_path.await {
+                Ok(Ok(Some(path))) => path,
+                Ok(Ok(None)) | Err(_) => return,
+                Ok(Err(err)) => {
+                    utils::error_notification("Failed to show the file dialog", err, cx);
+                    return;
+                }
+            };
+
+            // The extension that was typed in the dialog takes precedence over the chosen format
+            let (export_path, format) = match ExportFormat::from_path(&export_path) {
+                Ok(format) => (export_path, format),
+                Err(_) => {
+                    let mut path = export_path.into_os_string();
+                    path.push(".");
+                    path.push(format.extension());
+                    (PathBuf::from(path), format)
+                }
+            };
+
+            let _ = this.update(cx, |this, cx| this.start_loading(cx));
+            let result = cx
+                .background_executor()
+                .spawn({
+                    let export_path = export_path.clone();
+                    async move { tablewriter::write_table(&mut data, &export_path, format) }
+                })
+                .await;
+            let _ = this.update(cx, |this, cx| this.finish_loading(cx));
+
+            match result {
+                Ok(()) => utils::success_notification(
+                    format!("Exported to {}", export_path.display()),
+                    cx,
+                ),
+                Err(err) => utils::error_notification("Failed to export the table", err, cx),
+            }
+        })
+        .detach();
+    }
+
+    /// Write a profiling report of all the layers of the loaded file
+    fn on_action_profile_dataset(
+        &mut self,
+        _: &ProfileDataset,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(data_path) = self.data_path.clone() else {
+            return;
+        };
+
+        // The report covers all the rows, like the report of the command line
+        let options = ReadOptions {
+            row_limit: None,
+            ..Self::read_options(&data_path, cx)
+        };
+        let directory = data_path.parent().map(PathBuf::from).unwrap_or_default();
+        let suggested_name = data_path
+            .file_stem()
+            .map(|stem| format!("{}_profile.html", stem.to_string_lossy()));
+        let report_path = cx.prompt_for_new_path(&directory, suggested_name.as_deref());
+
+        cx.spawn(async move |this, cx| {
+            let report_path = match report_path.await {
+                Ok(Ok(Some(path))) => path,
+                Ok(Ok(None)) | Err(_) => return,
+                Ok(Err(err)) => {
+                    utils::error_notification("Failed to show the file dialog", err, cx);
+                    return;
+                }
+            };
+
+            let _ = this.update(cx, |this, cx| this.start_loading(cx));
+            let result = cx
+                .background_executor()
+                .spawn({
+                    let report_path = report_path.clone();
+                    async move { profile::write_report(&[data_path], &report_path, &options) }
+                })
+                .await;
+            let _ = this.update(cx, |this, cx| this.finish_loading(cx));
+
+            match result {
+                Ok(()) => utils::success_notification(
+                    format!("Profile report written to {}", report_path.display()),
+                    cx,
+                ),
+                Err(err) => utils::error_notification("Failed to profile the dataset", err, cx),
+            }
+        })
+        .detach();
+    }
+
+    /// Show the read options of the delimited text file, the options that were used last are
+    /// shown, or the detected options when the file was never reopened with options
+    fn on_action_reopen_with_options(
+        &mut self,
+        _: &ReopenWithOptions,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(path) = self
+            .data_path
+            .clone()
+            .filter(|path| tableio::is_delimited_text(path))
+        else {
+            return;
+        };
+
+        // The file was read with this encoding, it can differ from the one detected at the start
+        let read_options = Self::read_options(&path, cx);
+        let read_options = ReadOptions {
+            encoding: self.text_encoding.or(read_options.encoding),
+            ..read_options
+        };
+        let options = match tableio::csv_options(&path, &read_options) {
+            Ok(options) => options,
+            Err(err) => {
+                let message = utils::error_message("Failed to read the file", err);
+                window.push_notification(Notification::error(message), cx);
+                return;
+            }
+        };
+
+        let dialog = cx.new(|cx| CsvOptionsDialog::new(path, options, window, cx));
+        let _subscription = cx.subscribe_in(&dialog, window, Self::on_csv_dialog_event);
+        self.csv_dialog = Some(CsvDialogPanel {
+            dialog,
+            _subscription,
+        });
+        cx.notify();
+    }
+
+    fn on_csv_dialog_event(
+        &mut self,
+        _: &Entity<CsvOptionsDialog>,
+        event: &CsvDialogEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.csv_dialog = None;
+        if let CsvDialogEvent::Apply(options) = event
+            && let Some(path) = self.data_path.clone()
+        {
+            let settings = cx.global_mut::<CsvSettings>();
+            settings.set(&path, options.clone());
+            settings.save();
+
+            let layer = self.active_layer().map(|layer| layer.to_string());
+            self.open(path, layer, cx);
+        }
+
+        self.focus(window);
+        cx.notify();
+    }
+
+    fn on_drop_paths(
+        &mut self,
+        paths: &ExternalPaths,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(path) = paths.paths().first().cloned() else {
+            return;
+        };
+
+        cx.spawn(async move |this, cx| {
+            Self::open_if_supported(&this, path, cx);
+        })
+        .detach();
+    }
+
+    /// Open the path in the view, or notify the user when it is not a supported table format
+    fn open_if_supported(this: &WeakEntity<Self>, path: PathBuf, cx: &mut AsyncApp) {
+        if let Err(err) = tableio::check_supported_path(&path) {
+            utils::error_notification(&format!("Failed to open {}", path.display()), err, cx);
+            return;
+        }
+
+        let _ = this.update(cx, |this, cx| {
+            this.open(path, None, cx);
+        });
+    }
+
+    fn on_action_open_recent(
+        &mut self,
+        action: &OpenRecent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(recent) = cx
+            .global::<RecentFiles>()
+            .files()
+            .get(action.index)
+            .cloned()
+        {
+            self.open(recent.path, recent.layer, cx);
+        }
+    }
+
+    /// Replace the current table with the contents of the given file
+    /// The provided layer is activated if it is present in the file
+    pub fn open(&mut self, path: PathBuf, layer: Option<String>, cx: &mut Context<Self>) {
+        self.store_layer_state(cx);
+        // The file is read from disk again, it might have been modified
+        let document = cx.entity_id();
+        cx.global_mut::<LayerCache>().remove_file(document, &path);
+
+        self.active_tab = 0;
+        self.table_layer = None;
+        self.truncated_layers.clear();
+        self.csv_dialog = None;
+        self.query_results.clear();
+        self.query_count = 0;
+        self.query_error = None;
+        self.query_task = None;
+        self.table.update(cx, |table, cx| {
+            table.delegate_mut().clear();
+            table.refresh(cx);
+        });
+
+        Self::load_table(path, layer, cx).detach();
+        cx.notify();
+    }
+
+    fn load_table(path: PathBuf, layer: Option<String>, cx: &mut gpui::Context<Self>) -> Task<()> {
+        let options = Self::read_options(&path, cx);
         cx.spawn(async move |this, cx| {
+            let _ = this.update(cx, |this, cx| this.start_loading(cx));
             let path_clone = path.clone();
             let layers: Result<_> = cx
                 .background_executor()
-                .spawn(async move { Ok(tableio::layers_for_path(&path_clone)?) })
+                .spawn(async move {
+                    let file_size = tableio::data_size(&path_clone);
+                    let canonical_path =
+                        std::fs::canonicalize(&path_clone).unwrap_or_else(|_| path_clone.clone());
+                    let text_encoding = if tableio::is_delimited_text(&path_clone) {
+                        Some(tableio::csv_options(&path_clone, &options)?.encoding)
+                    } else {
+                        None
+                    };
+                    let layers = tableio::layers_for_path(&path_clone)?;
+                    Ok((layers, file_size, canonical_path, text_encoding))
+                })
                 .await;
+            let _ = this.update(cx, |this, cx| this.finish_loading(cx));
 
             match layers {
-                Ok(layers) => {
-                    let first_layer = layers.first().cloned();
+                Ok((layers, file_size, canonical_path, text_encoding)) => {
+                    let active_tab = layer
+                        .and_then(|layer| layers.iter().position(|name| *name == layer))
+                        .unwrap_or_default();
+                    let active_layer = layers.get(active_tab).cloned();
+
                     let _ = this.update(cx, |this, cx| {
+                        this.active_tab = active_tab;
                         this.data_path = Some(path.clone());
+                        this.canonical_path = Some(canonical_path);
+                        this.file_size = file_size;
+                        this.text_encoding = text_encoding;
                         this.layer_names = layers;
+                        this.update_recent_files(cx);
                         cx.notify();
                     });
 
-                    if let Some(layer) = first_layer {
+                    if let Some(layer) = active_layer {
                         let _ = this.update(cx, |_this, cx| {
-                            Self::load_table_layer(path, layer.to_string(), cx).detach();
+                            let options = Self::read_options(&path, cx);
+                            Self::load_table_layer(path, layer.to_string(), options, cx).detach();
                         });
                     }
                 }
@@ -83,19 +1057,175 @@ impl TableView {
         })
     }
 
-    fn load_table_layer(path: PathBuf, layer: String, cx: &mut gpui::Context<Self>) -> Task<()> {
+    /// Store the state of the active tab and show the tab with the given index
+    fn activate_tab(&mut self, tab: usize, cx: &mut gpui::Context<Self>) {
+        self.store_layer_state(cx);
+        self.show_tab(tab, cx);
+    }
+
+    /// Show a layer or query result tab, the state of the active tab is not stored
+    fn show_tab(&mut self, tab: usize, cx: &mut gpui::Context<Self>) {
+        self.active_tab = tab;
+        self.table_layer = None;
+        self.column_stats = None;
+        if let Some(snapshot) = self
+            .query_result_mut(tab)
+            .map(|result| result.snapshot.clone())
+        {
+            let search = self.search_text(cx);
+            self.table.update(cx, |table, cx| {
+                table.sortable = true;
+                table.delegate_mut().restore(snapshot);
+                table.delegate_mut().set_search(&search, cx);
+                table.refresh(cx);
+                cx.notify();
+            });
+        } else if let (Some(path), Some(layer)) =
+            (self.data_path.clone(), self.layer_names.get(tab).cloned())
+        {
+            self.update_recent_files(cx);
+            self.show_table_layer(path, layer.to_string(), cx);
+        }
+
+        cx.notify();
+    }
+
+    /// Show the layer from the cache, or load it from disk if it is not cached
+    fn show_table_layer(&mut self, path: PathBuf, layer: String, cx: &mut gpui::Context<Self>) {
+        let document = cx.entity_id();
+        if let Some(snapshot) = cx.global_mut::<LayerCache>().get(document, &path, &layer) {
+            self.load_time = None;
+            self.table_layer = Some(layer.into());
+            let search = self.search_text(cx);
+            self.table.update(cx, |table, cx| {
+                table.sortable = true;
+                table.delegate_mut().restore(snapshot);
+                table.delegate_mut().set_search(&search, cx);
+                table.refresh(cx);
+                cx.notify();
+            });
+        } else {
+            // Don't show the data of the previous tab while the layer is being read
+            self.table.update(cx, |table, cx| {
+                table.delegate_mut().clear();
+                table.refresh(cx);
+            });
+            let options = Self::read_options(&path, cx);
+            Self::load_table_layer(path, layer, options, cx).detach();
+        }
+    }
+
+    /// Store the data, filter and sort state of the active layer in the cache
+    /// The state of a query result is kept with the result itself
+    fn store_layer_state(&mut self, cx: &mut Context<Self>) {
+        let table = self.table.read(cx);
+        if table.delegate().loading(cx) {
+            return;
+        }
+
+        let snapshot = table.delegate().snapshot();
+        if let Some(result) = self.query_result_mut(self.active_tab) {
+            result.snapshot = snapshot;
+            return;
+        }
+
+        let (Some(path), Some(layer)) = (&self.data_path, self.layer_names.get(self.active_tab))
+        else {
+            return;
+        };
+        // The table still holds another layer when the active layer has not been read yet
+        if self.table_layer.as_ref() != Some(layer) {
+            return;
+        }
+
+        let document = cx.entity_id();
+        cx.global_mut::<LayerCache>()
+            .insert(document, path, layer.as_str(), snapshot);
+    }
+
+    /// The read options of the file, including the csv options that were chosen for it
+    fn read_options(path: &Path, cx: &App) -> ReadOptions {
+        ReadOptions {
+            csv: cx.global::<CsvSettings>().get(path).cloned(),
+            ..cx.global::<ReadOptions>().clone()
+        }
+    }
+
+    /// Read all the rows of the active layer when it was truncated by the database row limit
+    fn load_all_rows(&mut self, cx: &mut Context<Self>) {
+        let (Some(path), Some(layer)) = (self.data_path.clone(), self.table_layer.clone()) else {
+            return;
+        };
+
+        let options = ReadOptions {
+            row_limit: None,
+            ..Self::read_options(&path, cx)
+        };
+        Self::load_table_layer(path, layer.to_string(), options, cx).detach();
+    }
+
+    fn load_table_layer(
+        path: PathBuf,
+        layer: String,
+        options: ReadOptions,
+        cx: &mut gpui::Context<Self>,
+    ) -> Task<()> {
         cx.spawn(async move |this, cx| {
+            let _ = this.update(cx, |this, cx| this.start_loading(cx));
+            let start = Instant::now();
             // Move blocking I/O to a thread pool
             let layer_data = cx
                 .background_executor()
-                .spawn(async move { tableio::layer_data(&path, &layer) })
+                .spawn({
+                    let path = path.clone();
+                    let layer = layer.clone();
+                    async move { tableio::read_layer(&path, &layer, &options) }
+                })
                 .await;
+            let load_time = start.elapsed();
+            let _ = this.update(cx, |this, cx| this.finish_loading(cx));
+
             match layer_data {
-                Ok(data) => {
+                Ok(LayerData {
+                    data,
+                    truncated,
+                    encoding,
+                }) => {
                     let _ = this.update(cx, |this, cx| {
+                        // The encoding can differ from the one detected at the start of the file
+                        if encoding.is_some() {
+                            this.text_encoding = encoding;
+                        }
+                        if truncated {
+                            this.truncated_layers.insert(layer.clone().into());
+                        } else {
+                            this.truncated_layers.remove(layer.as_str());
+                        }
+
+                        let document = cx.entity_id();
+                        cx.global_mut::<LayerCache>().insert(
+                            document,
+                            &path,
+                            &layer,
+                            LayerSnapshot::new(data.clone()),
+                        );
+
+                        // The user might have switched to another layer in the meantime
+                        if this
+                            .layer_names
+                            .get(this.active_tab)
+                            .is_none_or(|name| *name != layer)
+                        {
+                            return;
+                        }
+
+                        this.load_time = Some(load_time);
+                        this.table_layer = Some(layer.into());
+                        let search = this.search_text(cx);
                         this.table.update(cx, |table, cx| {
                             table.sortable = true;
                             table.delegate_mut().update_data(data);
+                            table.delegate_mut().set_search(&search, cx);
                             table.refresh(cx);
                             cx.notify();
                         });
@@ -118,6 +1248,470 @@ impl TableView {
         })
     }
 
+    fn start_loading(&mut self, cx: &mut Context<Self>) {
+        self.pending_loads += 1;
+        cx.notify();
+    }
+
+    fn finish_loading(&mut self, cx: &mut Context<Self>) {
+        self.pending_loads = self.pending_loads.saturating_sub(1);
+        cx.notify();
+    }
+
+    /// Store the current file and layer at the top of the recent files list
+    fn update_recent_files(&self, cx: &mut App) {
+        if let Some(path) = &self.data_path {
+            let layer = self.layer_names.get(self.active_tab);
+            let recent = cx.global_mut::<RecentFiles>();
+            recent.add(path, layer.map(SharedString::as_str));
+            recent.save();
+        }
+    }
+
+    fn render_presets_menu(&self, cx: &App) -> impl IntoElement {
+        let presets: Vec<(usize, SharedString)> = match (&self.canonical_path, self.active_layer())
+        {
+            (Some(path), Some(layer)) => {
+                let presets = cx.global::<FilterPresets>();
+                presets
+                    .matching(path, layer)
+                    .into_iter()
+                    .map(|index| (index, presets.presets()[index].name.clone().into()))
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        let can_save = self.active_layer().is_some();
+        let focus_handle = self.focus_handle.clone();
+
+        Button::new("filter-presets")
+            .icon(IconName::Star)
+            .ghost()
+            .xsmall()
+            .tooltip("Filter presets")
+            .dropdown_menu(move |mut menu, _, _| {
+                menu = menu.action_context(focus_handle.clone());
+                for (index, name) in &presets {
+                    menu = menu.menu(name.clone(), Box::new(ApplyPreset { index: *index }));
+                }
+                if !presets.is_empty() {
+                    menu = menu.separator();
+                }
+                if can_save {
+                    menu = menu.menu("Save filters as preset…", Box::new(SavePreset));
+                }
+                menu
+            })
+    }
+
+    fn render_export_menu(&self) -> impl IntoElement {
+        let focus_handle = self.focus_handle.clone();
+
+        Button::new("export")
+            .icon(IconName::ArrowDown)
+            .ghost()
+            .xsmall()
+            .tooltip("Export view")
+            .dropdown_menu(move |mut menu, _, _| {
+                menu = menu.action_context(focus_handle.clone());
+                for (index, format) in ExportFormat::ALL.iter().enumerate() {
+                    menu = menu.menu(
+                        format!("Export as {}…", format.name()),
+                        Box::new(ExportAs { index }),
+                    );
+                }
+                menu
+            })
+    }
+
+    fn render_preset_editor(&self, cx: &mut gpui::Context<Self>) -> impl IntoElement {
+        h_flex()
+            .gap_1()
+            .p_1()
+            .child(
+                div()
+                    .w(px(200.))
+                    .child(Input::new(&self.preset_name_input).small()),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .child(Input::new(&self.preset_file_input).small()),
+            )
+            .child(
+                Button::new("save-preset")
+                    .label("Save preset")
+                    .primary()
+                    .small()
+                    .on_click(cx.listener(|this, _, window, cx| this.save_preset(window, cx))),
+            )
+            .child(
+                Button::new("cancel-preset")
+                    .label("Cancel")
+                    .ghost()
+                    .small()
+                    .on_click(
+                        cx.listener(|this, _, window, cx| this.close_preset_editor(window, cx)),
+                    ),
+            )
+    }
+
+    fn render_recent_files_menu(&self, cx: &App) -> impl IntoElement {
+        let recent_paths: Vec<SharedString> = cx
+            .global::<RecentFiles>()
+            .files()
+            .iter()
+            .map(|file| file.path.display().to_string().into())
+            .collect();
+        let focus_handle = self.focus_handle.clone();
+
+        Button::new("recent-files")
+            .icon(IconName::FolderOpen)
+            .ghost()
+            .xsmall()
+            .tooltip("Open recent")
+            .dropdown_menu(move |mut menu, _, _| {
+                menu = menu.action_context(focus_handle.clone());
+                for (index, path) in recent_paths.iter().enumerate() {
+                    menu = menu.menu(path.clone(), Box::new(OpenRecent { index }));
+                }
+                menu
+            })
+    }
+
+    fn render_recent_files(&self, cx: &mut gpui::Context<Self>) -> impl IntoElement {
+        let recent_files = cx.global::<RecentFiles>().files().to_vec();
+
+        v_flex()
+            .mt_4()
+            .gap_1()
+            .items_center()
+            .when(!recent_files.is_empty(), |this| this.child("Recent files"))
+            .children(
+                recent_files
+                    .into_iter()
+                    .take(9)
+                    .enumerate()
+                    .map(|(index, file)| {
+                        h_flex()
+                            .gap_2()
+                            .child(Kbd::new(
+                                Keystroke::parse(&(index + 1).to_string()).unwrap(),
+                            ))
+                            .child(
+                                Button::new(("recent-file", index))
+                                    .ghost()
+                                    .small()
+                                    .label(file.path.display().to_string())
+                                    .on_click(cx.listener(move |this, _, window, cx| {
+                                        this.on_action_open_recent(
+                                            &OpenRecent { index },
+                                            window,
+                                            cx,
+                                        );
+                                    })),
+                            )
+                    }),
+            )
+    }
+
+    fn render_search_bar(&self, cx: &mut gpui::Context<Self>) -> impl IntoElement {
+        h_flex()
+            .gap_1()
+            .p_1()
+            .child(
+                Input::new(&self.search_input)
+                    .prefix(Icon::new(IconName::Search))
+                    .small(),
+            )
+            .child(
+                Button::new("search-previous")
+                    .icon(IconName::ChevronUp)
+                    .ghost()
+                    .small()
+                    .tooltip("Previous match (shift-enter)")
+                    .on_click(cx.listener(|this, _, _, cx| this.select_next_match(false, cx))),
+            )
+            .child(
+                Button::new("search-next")
+                    .icon(IconName::ChevronDown)
+                    .ghost()
+                    .small()
+                    .tooltip("Next match (enter)")
+                    .on_click(cx.listener(|this, _, _, cx| this.select_next_match(true, cx))),
+            )
+    }
+
+    fn render_query_history_menu(&self, cx: &App) -> impl IntoElement {
+        // Only the first line of each query is shown in the menu
+        let queries: Vec<SharedString> = cx
+            .global::<QueryHistory>()
+            .queries()
+            .iter()
+            .map(|query| query.lines().next().unwrap_or_default().to_string().into())
+            .collect();
+        let focus_handle = self.focus_handle.clone();
+
+        Button::new("query-history")
+            .label("History")
+            .ghost()
+            .small()
+            .disabled(queries.is_empty())
+            .dropdown_menu(move |mut menu, _, _| {
+                menu = menu.action_context(focus_handle.clone());
+                for (index, query) in queries.iter().enumerate() {
+                    menu = menu.menu(query.clone(), Box::new(SelectQuery { index }));
+                }
+                menu
+            })
+    }
+
+    fn render_query_panel(&self, cx: &mut gpui::Context<Self>) -> impl IntoElement {
+        #[cfg(target_os = "macos")]
+        let run_hint = "Run query (cmd-enter)";
+        #[cfg(not(target_os = "macos"))]
+        let run_hint = "Run query (ctrl-enter)";
+
+        v_flex()
+            .gap_1()
+            .p_1()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(Input::new(&self.query_input).h(px(120.)))
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new("run-query")
+                            .label("Run")
+                            .primary()
+                            .small()
+                            .loading(self.query_task.is_some())
+                            .tooltip(run_hint)
+                            .on_click(cx.listener(|this, _, _, cx| this.run_query(cx))),
+                    )
+                    .child(self.render_query_history_menu(cx))
+                    .when_some(self.query_error.clone(), |this, error| {
+                        this.child(
+                            div()
+                                .flex_1()
+                                .text_xs()
+                                .text_color(cx.theme().danger)
+                                .child(error),
+                        )
+                    }),
+            )
+    }
+
+    fn render_status_bar(&self, cx: &mut gpui::Context<Self>) -> impl IntoElement {
+        let layer = self.table.read(cx).delegate();
+        let (total_rows, visible_rows) = (layer.total_rows(), layer.visible_rows());
+        let busy = self.pending_loads > 0 || layer.is_busy();
+        let summary = layer.view_state().summary();
+
+        let rows = if visible_rows == total_rows {
+            format!("{total_rows} rows")
+        } else {
+            format!("{visible_rows} of {total_rows} rows")
+        };
+        // A single selected cell is the cursor, it is not worth mentioning
+        let selected = layer.selection().and_then(|selection| {
+            let (rows, columns) = (selection.rows().len(), selection.columns().len());
+            if rows * columns <= 1 {
+                None
+            } else if columns == layer.column_count() {
+                Some(format!("{rows} rows selected"))
+            } else {
+                Some(format!("{} cells selected", rows * columns))
+            }
+        });
+
+        let truncated = self
+            .table_layer
+            .as_ref()
+            .is_some_and(|layer| self.truncated_layers.contains(layer));
+
+        h_flex()
+            .gap_3()
+            .px_2()
+            .py_1()
+            .text_xs()
+            .text_color(cx.theme().muted_foreground)
+            .border_t_1()
+            .border_color(cx.theme().border)
+            .child(rows)
+            .child(format!("{} columns", layer.column_count()))
+            .when_some(selected, |this, selected| this.child(selected))
+            .when(truncated, |this| {
+                this.child("Only the first rows of the table were read")
+                    .child(
+                        Button::new("load-all-rows")
+                            .label("Load all rows")
+                            .ghost()
+                            .xsmall()
+                            .on_click(cx.listener(|this, _, _, cx| this.load_all_rows(cx))),
+                    )
+            })
+            .when_some(self.file_size, |this, size| {
+                this.child(utils::format_size(size))
+            })
+            .when_some(self.text_encoding, |this, encoding| {
+                this.child(encoding.name())
+            })
+            .when_some(self.load_time, |this, load_time| {
+                this.child(format!("Loaded in {:.2}s", load_time.as_secs_f64()))
+            })
+            .when(!summary.is_empty(), |this| {
+                this.child(div().overflow_hidden().text_ellipsis().child(summary))
+            })
+            .child(div().flex_1())
+            .when(busy, |this| this.child(Spinner::new().xsmall()))
+    }
+
+    fn render_column_stats(&self, cx: &mut gpui::Context<Self>) -> Option<impl IntoElement> {
+        let panel = self.column_stats.as_ref()?;
+
+        let header = h_flex()
+            .gap_1()
+            .child(
+                div()
+                    .flex_1()
+                    .font_semibold()
+                    .overflow_hidden()
+                    .text_ellipsis()
+                    .child(panel.column.clone()),
+            )
+            .child(
+                Button::new("close-column-stats")
+                    .icon(IconName::Close)
+                    .ghost()
+                    .xsmall()
+                    .on_click(cx.listener(|this, _, _, cx| this.close_column_stats(cx))),
+            );
+
+        let content = match &panel.stats {
+            Some(stats) => Self::render_stats(stats, cx).into_any_element(),
+            None => h_flex()
+                .justify_center()
+                .p_4()
+                .child(Spinner::new())
+                .into_any_element(),
+        };
+
+        Some(
+            v_flex()
+                .id("column-stats")
+                .w(px(280.))
+                .h_full()
+                .flex_none()
+                .gap_2()
+                .p_2()
+                .text_xs()
+                .border_l_1()
+                .border_color(cx.theme().border)
+                .overflow_y_scroll()
+                .child(header)
+                .child(content),
+        )
+    }
+
+    fn render_stats(stats: &ColumnStats, cx: &App) -> impl IntoElement {
+        let stat_row = |label: SharedString, value: String| {
+            h_flex()
+                .justify_between()
+                .gap_2()
+                .child(div().text_color(cx.theme().muted_foreground).child(label))
+                .child(value)
+        };
+
+        let mut rows = v_flex()
+            .gap_0p5()
+            .child(stat_row("Type".into(), stats.dtype.to_string()))
+            .child(stat_row("Rows".into(), stats.count.to_string()))
+            .child(stat_row("Missing".into(), stats.null_count.to_string()))
+            .child(stat_row(
+                "Distinct".into(),
+                stats.distinct_count.to_string(),
+            ));
+
+        if let Some(numeric) = &stats.numeric {
+            rows = rows
+                .child(stat_row(
+                    "Min".into(),
+                    columnstats::format_number(numeric.min),
+                ))
+                .child(stat_row(
+                    "Max".into(),
+                    columnstats::format_number(numeric.max),
+                ))
+                .child(stat_row(
+                    "Mean".into(),
+                    columnstats::format_number(numeric.mean),
+                ))
+                .child(stat_row(
+                    "Median".into(),
+                    columnstats::format_number(numeric.median),
+                ))
+                .when_some(numeric.std_dev, |this, std_dev| {
+                    this.child(stat_row(
+                        "Std. deviation".into(),
+                        columnstats::format_number(std_dev),
+                    ))
+                })
+                .children(numeric.quantiles.iter().map(|(quantile, value)| {
+                    stat_row(
+                        format!("{:.0}%", quantile * 100.0).into(),
+                        columnstats::format_number(*value),
+                    )
+                }));
+        }
+
+        let histogram_title = if stats.numeric.is_some() {
+            "Histogram"
+        } else {
+            "Most frequent values"
+        };
+        let max_count = stats
+            .histogram
+            .iter()
+            .map(|bin| bin.count)
+            .max()
+            .unwrap_or_default()
+            .max(1);
+
+        let histogram = v_flex()
+            .gap_0p5()
+            .child(div().font_semibold().child(histogram_title))
+            .children(stats.histogram.iter().map(|bin| {
+                v_flex()
+                    .child(
+                        h_flex()
+                            .justify_between()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .overflow_hidden()
+                                    .text_ellipsis()
+                                    .child(bin.label.clone()),
+                            )
+                            .child(
+                                div()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(bin.count.to_string()),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .h_1()
+                            .rounded(cx.theme().radius)
+                            .bg(cx.theme().primary)
+                            .w(relative(bin.count as f32 / max_count as f32)),
+                    )
+            }));
+
+        v_flex().gap_3().child(rows).child(histogram)
+    }
+
     fn render_tab_content(
         &self,
         _window: &mut Window,
@@ -138,46 +1732,137 @@ impl Render for TableView {
             let shortcut_hint = "cmd+o";
             #[cfg(not(target_os = "macos"))]
             let shortcut_hint = "ctrl-o";
-            return div().grid().size_full().content_center().child(
-                v_flex()
-                    .font_bold()
-                    .text_center()
-                    .child("No data loaded")
-                    .child(
-                        h_flex()
-                            .justify_center()
-                            .debug_pink()
-                            .gap_2()
-                            .child("Press")
-                            .child(Kbd::new(Keystroke::parse(shortcut_hint).unwrap()))
-                            .child("to open a file"),
-                    ),
-            );
+            return div()
+                .track_focus(&self.focus_handle)
+                .key_context(RECENT_FILES_CONTEXT)
+                .on_action(cx.listener(Self::on_action_open))
+                .on_action(cx.listener(Self::on_action_open_directory))
+                .on_action(cx.listener(Self::on_action_open_recent))
+                .on_drop(cx.listener(Self::on_drop_paths))
+                .drag_over::<ExternalPaths>(|style, _, _, cx| style.bg(cx.theme().drop_target))
+                .grid()
+                .size_full()
+                .content_center()
+                .child(
+                    v_flex()
+                        .font_bold()
+                        .text_center()
+                        .child("No data loaded")
+                        .child(
+                            h_flex()
+                                .justify_center()
+                                .debug_pink()
+                                .gap_2()
+                                .child("Press")
+                                .child(Kbd::new(Keystroke::parse(shortcut_hint).unwrap()))
+                                .child("to open a file"),
+                        ),
+                );
         }
 
+        let can_reopen_with_options = self
+            .data_path
+            .as_deref()
+            .is_some_and(tableio::is_delimited_text);
         let mut tab_bar = TabBar::new("layers")
             .selected_index(self.active_tab)
             .on_click(cx.listener(|view, index, _, cx| {
-                view.active_tab = *index;
-                let path = view.data_path.clone().unwrap();
-                let layer_name = view.layer_names[*index].to_string();
-                Self::load_table_layer(path, layer_name, cx).detach();
-                cx.notify();
-            }));
+                view.activate_tab(*index, cx);
+            }))
+            .suffix(
+                h_flex()
+                    .when(can_reopen_with_options, |this| {
+                        this.child(
+                            Button::new("reopen-with-options")
+                                .icon(IconName::Settings)
+                                .ghost()
+                                .xsmall()
+                                .tooltip("Reopen with options…")
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.on_action_reopen_with_options(
+                                        &ReopenWithOptions,
+                                        window,
+                                        cx,
+                                    )
+                                })),
+                        )
+                    })
+                    .child(
+                        Button::new("profile-dataset")
+                            .icon(IconName::ChartPie)
+                            .ghost()
+                            .xsmall()
+                            .tooltip("Profile dataset…")
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.on_action_profile_dataset(&ProfileDataset, window, cx)
+                            })),
+                    )
+                    .child(self.render_export_menu())
+                    .child(self.render_presets_menu(cx))
+                    .child(self.render_recent_files_menu(cx)),
+            );
 
         for layer in &self.layer_names {
             tab_bar = tab_bar.child(Tab::new().label(layer.clone()));
         }
 
+        for (index, result) in self.query_results.iter().enumerate() {
+            tab_bar = tab_bar.child(
+                Tab::new().label(result.name.clone()).suffix(
+                    Button::new(("close-query-result", index))
+                        .icon(IconName::Close)
+                        .ghost()
+                        .xsmall()
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            cx.stop_propagation();
+                            this.close_query_result(index, cx);
+                        })),
+                ),
+            );
+        }
+
         v_flex()
+            .track_focus(&self.focus_handle)
             .size_full()
+            .when(self.query_visible, |this| {
+                this.child(self.render_query_panel(cx))
+            })
+            .when(self.search_visible, |this| {
+                this.child(self.render_search_bar(cx))
+            })
+            .when(self.preset_editor_visible, |this| {
+                this.child(self.render_preset_editor(cx))
+            })
+            .when_some(self.csv_dialog.as_ref(), |this, panel| {
+                this.child(panel.dialog.clone())
+            })
             .child(
-                div()
+                h_flex()
                     .flex_1()
                     .size_full()
-                    .child(self.render_tab_content(window, cx)),
+                    .child(self.render_tab_content(window, cx))
+                    .children(self.render_column_stats(cx)),
             )
             .child(tab_bar)
+            .child(self.render_status_bar(cx))
+            .on_action(cx.listener(Self::on_action_toggle_filter))
             .on_action(cx.listener(Self::on_action_toggle_search))
+            .on_action(cx.listener(Self::on_action_toggle_query_panel))
+            .on_action(cx.listener(Self::on_action_select_query))
+            .on_action(cx.listener(Self::on_action_apply_preset))
+            .on_action(cx.listener(Self::on_action_save_preset))
+            .on_action(cx.listener(Self::on_action_profile_dataset))
+            .on_action(cx.listener(Self::on_action_reopen_with_options))
+            .on_action(cx.listener(Self::on_action_export_as))
+            .on_action(cx.listener(Self::on_action_copy_selection))
+            .on_action(cx.listener(Self::on_action_drill_down))
+            .on_action(cx.listener(Self::on_action_copy_selection_as))
+            .on_action(cx.listener(Self::on_action_select_rows))
+            .on_action(cx.listener(Self::on_action_select_columns))
+            .on_action(cx.listener(Self::on_action_open))
+            .on_action(cx.listener(Self::on_action_open_directory))
+            .on_action(cx.listener(Self::on_action_open_recent))
+            .on_drop(cx.listener(Self::on_drop_paths))
+            .drag_over::<ExternalPaths>(|style, _, _, cx| style.bg(cx.theme().drop_target))
     }
 }
@@ -0,0 +1,286 @@
+use anyhow::{Context, Result, bail};
+use polars::frame::DataFrame;
+use polars::prelude::{
+    AnyValue, CsvWriter, IpcWriter, JsonFormat, JsonWriter, ParquetWriter, SerWriter, TimeUnit,
+};
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
+use std::fs::File;
+use std::path::Path;
+
+/// The maximum number of rows of an xlsx worksheet, including the header row
+const XLSX_MAX_ROWS: usize = 1_048_576;
+const XLSX_MAX_COLUMNS: usize = 16_384;
+/// Worksheet numbers are f64 values, larger integers are written as text to keep all the digits
+const XLSX_MAX_EXACT_INTEGER: u64 = 1 << 53;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Tsv,
+    Parquet,
+    Ipc,
+    Ndjson,
+    Xlsx,
+}
+
+impl ExportFormat {
+    pub const ALL: &[ExportFormat] = &[
+        ExportFormat::Csv,
+        ExportFormat::Tsv,
+        ExportFormat::Parquet,
+        ExportFormat::Ipc,
+        ExportFormat::Ndjson,
+        ExportFormat::Xlsx,
+    ];
+
+    /// The file extension (lowercase) that is used for the format
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Tsv => "tsv",
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::Ipc => "arrow",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Tsv => "TSV",
+            ExportFormat::Parquet => "Parquet",
+            ExportFormat::Ipc => "Arrow IPC",
+            ExportFormat::Ndjson => "NDJSON",
+            ExportFormat::Xlsx => "Excel",
+        }
+    }
+
+    /// Determine the format from the extension of the path
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            Some("csv") => Ok(ExportFormat::Csv),
+            Some("tsv" | "tab") => Ok(ExportFormat::Tsv),
+            Some("parquet") => Ok(ExportFormat::Parquet),
+            Some("arrow" | "ipc" | "feather") => Ok(ExportFormat::Ipc),
+            Some("ndjson" | "jsonl") => Ok(ExportFormat::Ndjson),
+            Some("xlsx") => Ok(ExportFormat::Xlsx),
+            _ => bail!(
+                "Unsupported export format, supported formats: {}",
+                ExportFormat::ALL
+                    .iter()
+                    .map(|format| format.extension())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+/// Write the data to the path in the given format
+pub fn write_table(data: &mut DataFrame, path: &Path, format: ExportFormat) -> Result<()> {
+    log::debug!("Write table: {}", path.display());
+    match format {
+        ExportFormat::Csv => CsvWriter::new(create_file(path)?)
+            .include_header(true)
+            .finish(data)?,
+        ExportFormat::Tsv => CsvWriter::new(create_file(path)?)
+            .include_header(true)
+            .with_separator(b'\t')
+            .finish(data)?,
+        ExportFormat::Parquet => {
+            ParquetWriter::new(create_file(path)?).finish(data)?;
+        }
+        ExportFormat::Ipc => IpcWriter::new(create_file(path)?).finish(data)?,
+        ExportFormat::Ndjson => JsonWriter::new(create_file(path)?)
+            .with_json_format(JsonFormat::JsonLines)
+            .finish(data)?,
+        ExportFormat::Xlsx => write_xlsx(data, path)?,
+    }
+
+    Ok(())
+}
+
+fn create_file(path: &Path) -> Result<File> {
+    File::create(path).with_context(|| format!("Failed to create {}", path.display()))
+}
+
+fn write_xlsx(data: &DataFrame, path: &Path) -> Result<()> {
+    if data.height() >= XLSX_MAX_ROWS || data.width() > XLSX_MAX_COLUMNS {
+        bail!(
+            "The table is too large for an xlsx worksheet ({} rows, {} columns)",
+            data.height(),
+            data.width()
+        );
+    }
+
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    let header_format = Format::new().set_bold();
+    let date_format = Format::new().set_num_format("yyyy-mm-dd");
+    let datetime_format = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
+
+    for (col_ix, column) in data.get_columns().iter().enumerate() {
+        let col = col_ix as u16;
+        worksheet.write_string_with_format(0, col, column.name().as_str(), &header_format)?;
+
+        let series = column.as_materialized_series().rechunk();
+        for (row_ix, value) in series.iter().enumerate() {
+            let row = row_ix as u32 + 1;
+            match value {
+                AnyValue::Null => {}
+                AnyValue::Boolean(value) => {
+                    worksheet.write_boolean(row, col, value)?;
+                }
+                AnyValue::String(value) => {
+                    worksheet.write_string(row, col, value)?;
+                }
+                AnyValue::StringOwned(value) => {
+                    worksheet.write_string(row, col, value.as_str())?;
+                }
+                AnyValue::Int64(value) if value.unsigned_abs() > XLSX_MAX_EXACT_INTEGER => {
+                    worksheet.write_string(row, col, value.to_string())?;
+                }
+                AnyValue::UInt64(value) if value > XLSX_MAX_EXACT_INTEGER => {
+                    worksheet.write_string(row, col, value.to_string())?;
+                }
+                value @ (AnyValue::Date(_)
+                | AnyValue::Datetime(..)
+                | AnyValue::DatetimeOwned(..)) => {
+                    let format = if matches!(value, AnyValue::Date(_)) {
+                        &date_format
+                    } else {
+                        &datetime_format
+                    };
+                    match excel_datetime(&value) {
+                        Some(datetime) => {
+                            worksheet.write_datetime_with_format(row, col, &datetime, format)?;
+                        }
+                        None => {
+                            worksheet.write_string(row, col, value.to_string())?;
+                        }
+                    }
+                }
+                value if value.is_primitive_numeric() => {
+                    let number = value.extract::<f64>().unwrap_or(f64::NAN);
+                    worksheet.write_number(row, col, number)?;
+                }
+                value => {
+                    worksheet.write_string(row, col, value.to_string())?;
+                }
+            }
+        }
+    }
+
+    workbook
+        .save(path)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// The date or datetime value as a worksheet datetime, the time zone is ignored
+/// Returns `None` for other values and for dates that a worksheet can't represent
+fn excel_datetime(value: &AnyValue) -> Option<ExcelDateTime> {
+    let seconds = match value {
+        AnyValue::Date(days) => i64::from(*days) * 86_400,
+        AnyValue::Datetime(value, unit, _) | AnyValue::DatetimeOwned(value, unit, _) => {
+            let per_second = match unit {
+                TimeUnit::Nanoseconds => 1_000_000_000,
+                TimeUnit::Microseconds => 1_000_000,
+                TimeUnit::Milliseconds => 1_000,
+            };
+            value.div_euclid(per_second)
+        }
+        _ => return None,
+    };
+
+    ExcelDateTime::from_timestamp(seconds).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::{people, temp_path};
+    use polars::prelude::*;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ExportFormat::from_path(Path::new("out.CSV")).unwrap(),
+            ExportFormat::Csv
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("out.jsonl")).unwrap(),
+            ExportFormat::Ndjson
+        );
+        assert!(ExportFormat::from_path(Path::new("out.txt")).is_err());
+
+        for format in ExportFormat::ALL {
+            let path = format!("out.{}", format.extension());
+            assert_eq!(ExportFormat::from_path(Path::new(&path)).unwrap(), *format);
+        }
+    }
+
+    #[test]
+    fn test_write_formats() {
+        for format in ExportFormat::ALL {
+            let path = temp_path(&format!("export.{}", format.extension()));
+            write_table(&mut people(), &path, *format).unwrap();
+            assert!(path.metadata().unwrap().len() > 0);
+            std::fs::remove_file(path).ok();
+        }
+    }
+
+    #[test]
+    fn test_write_tsv() {
+        let path = temp_path("export_tab.tsv");
+        write_table(&mut people(), &path, ExportFormat::Tsv).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            contents,
+            "name\tcity\tage\tscore\tactive\n\
+             Alice\tGent\t31\t1.5\ttrue\n\
+             Bob\tBrussel\t42\t2.0\tfalse\n\
+             alicia\t\t25\t3.25\ttrue\n\
+             Charlie\tAntwerpen\t31\t0.5\ttrue\n\
+             O'Brien, Dave\tGent\t8\t4.75\tfalse\n"
+        );
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_write_xlsx_dates_and_large_integers() {
+        let mut data = DataFrame::new(vec![
+            Series::new("day".into(), [0, 19_000])
+                .cast(&DataType::Date)
+                .unwrap()
+                .into_column(),
+            Series::new("time".into(), [0i64, 1_700_000_000_000])
+                .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))
+                .unwrap()
+                .into_column(),
+            Column::new("id".into(), [1i64, 9_007_199_254_740_993]),
+        ])
+        .unwrap();
+
+        let path = temp_path("export_dates.xlsx");
+        write_table(&mut data, &path, ExportFormat::Xlsx).unwrap();
+        assert!(path.metadata().unwrap().len() > 0);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_excel_datetime() {
+        assert!(excel_datetime(&AnyValue::Date(19_000)).is_some());
+        assert!(excel_datetime(&AnyValue::Datetime(0, TimeUnit::Microseconds, None)).is_some());
+        assert!(excel_datetime(&AnyValue::Int32(1)).is_none());
+
+        // Dates before 1900 can't be represented in a worksheet
+        assert!(excel_datetime(&AnyValue::Date(-30_000)).is_none());
+    }
+}
@@ -1,25 +1,193 @@
 use gpui::*;
 use gpui::{App, IntoElement, Window};
+use gpui_component::button::{Button, ButtonVariants as _};
+use gpui_component::tab::{Tab, TabBar};
 use gpui_component::*;
 use std::path::PathBuf;
 
 use crate::tableview::TableView;
 
-actions!(story, [Open, Quit, ToggleFilter,]);
+actions!(
+    story,
+    [
+        Open,
+        OpenDirectory,
+        Quit,
+        ToggleFilter,
+        ToggleSearch,
+        ToggleQueryPanel,
+        SavePreset,
+        ProfileDataset,
+        ReopenWithOptions,
+        CopySelection,
+        SelectRows,
+        SelectColumns,
+        NewTab,
+        CloseTab,
+    ]
+);
 
+/// Reopen the entry at the given index of the recent files list
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = story, no_json)]
+pub struct OpenRecent {
+    pub index: usize,
+}
+
+/// Put the query at the given index of the query history in the query editor
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = story, no_json)]
+pub struct SelectQuery {
+    pub index: usize,
+}
+
+/// Apply the filter preset at the given index of the saved presets
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = story, no_json)]
+pub struct ApplyPreset {
+    pub index: usize,
+}
+
+/// Export the current view in the format at the given index of the export formats
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = story, no_json)]
+pub struct ExportAs {
+    pub index: usize,
+}
+
+/// Copy the selected cells in the format at the given index of the copy formats
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = story, no_json)]
+pub struct CopySelectionAs {
+    pub index: usize,
+}
+
+/// Show the items of the list in the cell in a separate tab
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = story, no_json)]
+pub struct DrillDown {
+    pub row_ix: usize,
+    pub col_ix: usize,
+}
+
+/// The application root, every document tab owns its own table view
 pub struct Tabulite {
-    table: Entity<TableView>,
+    documents: Vec<Entity<TableView>>,
+    active_document: usize,
+    subscriptions: Vec<Subscription>,
 }
 
 impl Tabulite {
-    pub fn view(path: Option<PathBuf>, window: &mut Window, cx: &mut App) -> Entity<Self> {
-        cx.new(|cx| Self::new(path, window, cx))
+    pub fn view(paths: Vec<PathBuf>, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(paths, window, cx))
     }
 
-    fn new(path: Option<PathBuf>, window: &mut Window, cx: &mut gpui::Context<Self>) -> Self {
-        let table = TableView::view(path, window, cx);
+    fn new(paths: Vec<PathBuf>, window: &mut Window, cx: &mut gpui::Context<Self>) -> Self {
+        let mut tabulite = Self {
+            documents: Vec::new(),
+            active_document: 0,
+            subscriptions: Vec::new(),
+        };
+
+        if paths.is_empty() {
+            tabulite.add_document(None, window, cx);
+        } else {
+            for path in paths {
+                tabulite.add_document(Some(path), window, cx);
+            }
+        }
+
+        tabulite.activate_document(0, window, cx);
+        tabulite
+    }
+
+    fn add_document(
+        &mut self,
+        path: Option<PathBuf>,
+        window: &mut Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        let document = TableView::view(path, window, cx);
+        // Keep the tab titles in sync with the loaded files
+        self.subscriptions
+            .push(cx.observe(&document, |_, _, cx| cx.notify()));
+        self.documents.push(document);
+    }
+
+    fn activate_document(
+        &mut self,
+        index: usize,
+        window: &mut Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        self.active_document = index.min(self.documents.len().saturating_sub(1));
+        if let Some(document) = self.documents.get(self.active_document) {
+            document.read(cx).focus(window);
+        }
+        cx.notify();
+    }
+
+    fn close_document(&mut self, index: usize, window: &mut Window, cx: &mut gpui::Context<Self>) {
+        if index >= self.documents.len() {
+            return;
+        }
+
+        self.documents.remove(index);
+        self.subscriptions.remove(index);
+        if self.documents.is_empty() {
+            self.add_document(None, window, cx);
+        }
+
+        let active = if index < self.active_document {
+            self.active_document - 1
+        } else {
+            self.active_document
+        };
+        self.activate_document(active, window, cx);
+    }
+
+    fn on_action_new_tab(&mut self, _: &NewTab, window: &mut Window, cx: &mut Context<Self>) {
+        self.add_document(None, window, cx);
+        self.activate_document(self.documents.len() - 1, window, cx);
+    }
+
+    fn on_action_close_tab(&mut self, _: &CloseTab, window: &mut Window, cx: &mut Context<Self>) {
+        self.close_document(self.active_document, window, cx);
+    }
+
+    fn render_document_tabs(&self, cx: &mut gpui::Context<Self>) -> impl IntoElement {
+        let mut tab_bar = TabBar::new("documents")
+            .selected_index(self.active_document)
+            .on_click(cx.listener(|this, index, window, cx| {
+                this.activate_document(*index, window, cx);
+            }))
+            .suffix(
+                Button::new("new-document")
+                    .icon(IconName::Plus)
+                    .ghost()
+                    .xsmall()
+                    .tooltip("New tab")
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.on_action_new_tab(&NewTab, window, cx);
+                    })),
+            );
+
+        for (index, document) in self.documents.iter().enumerate() {
+            tab_bar = tab_bar.child(
+                Tab::new().label(document.read(cx).title()).suffix(
+                    Button::new(("close-document", index))
+                        .icon(IconName::Close)
+                        .ghost()
+                        .xsmall()
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            cx.stop_propagation();
+                            this.close_document(index, window, cx);
+                        })),
+                ),
+            );
+        }
 
-        Self { table }
+        tab_bar
     }
 }
 
@@ -30,7 +198,15 @@ impl Render for Tabulite {
         div()
             .v_flex()
             .size_full()
-            .child(self.table.clone())
+            .child(self.render_document_tabs(cx))
+            .child(
+                div()
+                    .flex_1()
+                    .min_h_0()
+                    .children(self.documents.get(self.active_document).cloned()),
+            )
             .children(notification_layer)
+            .on_action(cx.listener(Self::on_action_new_tab))
+            .on_action(cx.listener(Self::on_action_close_tab))
     }
 }
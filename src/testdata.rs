@@ -0,0 +1,23 @@
+//! Data shared by the unit tests
+
+use polars::prelude::*;
+use std::path::PathBuf;
+
+/// A small table with a column of every basic type
+/// The city of `alicia` is missing and the name of the last row contains a quote and a comma
+pub fn people() -> DataFrame {
+    df!(
+        "name" => ["Alice", "Bob", "alicia", "Charlie", "O'Brien, Dave"],
+        "city" => [Some("Gent"), Some("Brussel"), None, Some("Antwerpen"), Some("Gent")],
+        "age" => [31, 42, 25, 31, 8],
+        "score" => [1.5, 2.0, 3.25, 0.5, 4.75],
+        "active" => [true, false, true, true, false],
+    )
+    .unwrap()
+}
+
+/// A path in the temp directory that includes the process id, so concurrent test runs do not
+/// use the same files
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("tabulite_test_{}_{name}", std::process::id()))
+}
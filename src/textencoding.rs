@@ -0,0 +1,225 @@
+use chardetng::EncodingDetector;
+use encoding_rs::{Decoder, Encoding};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, Read};
+use std::str::FromStr;
+
+/// The number of bytes that is inspected for the zero bytes of UTF-16 text without byte order mark
+const UTF16_SAMPLE_SIZE: usize = 1024;
+/// The number of bytes that is read at once when the encoding of a complete file is detected
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The character encoding of a text file, identified by its WHATWG name (e.g. `windows-1252`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextEncoding(&'static Encoding);
+
+impl TextEncoding {
+    pub const UTF_8: TextEncoding = TextEncoding(encoding_rs::UTF_8);
+
+    /// The encodings that are offered when reopening a file, latin-1 is read as windows-1252
+    pub const COMMON: &[TextEncoding] = &[
+        TextEncoding::UTF_8,
+        TextEncoding(encoding_rs::UTF_16LE),
+        TextEncoding(encoding_rs::UTF_16BE),
+        TextEncoding(encoding_rs::WINDOWS_1252),
+        TextEncoding(encoding_rs::ISO_8859_15),
+        TextEncoding(encoding_rs::WINDOWS_1250),
+    ];
+
+    /// The encoding for a label like `utf-8`, `latin1` or `cp1252`
+    pub fn for_label(label: &str) -> Option<Self> {
+        Encoding::for_label(label.trim().as_bytes()).map(TextEncoding)
+    }
+
+    pub fn name(self) -> &'static str {
+        self.0.name()
+    }
+
+    /// Decode the text to UTF-8, a byte order mark is removed and invalid sequences are replaced
+    pub fn decode(self, bytes: &[u8]) -> Cow<'_, str> {
+        self.0.decode_with_bom_removal(bytes).0
+    }
+
+    /// A decoder to decode text in chunks, it removes a byte order mark like [`Self::decode`]
+    pub fn new_decoder(self) -> Decoder {
+        self.0.new_decoder_with_bom_removal()
+    }
+}
+
+impl Default for TextEncoding {
+    fn default() -> Self {
+        TextEncoding::UTF_8
+    }
+}
+
+impl fmt::Display for TextEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for TextEncoding {
+    type Err = String;
+
+    fn from_str(label: &str) -> Result<Self, Self::Err> {
+        TextEncoding::for_label(label).ok_or_else(|| format!("Unknown encoding: {label}"))
+    }
+}
+
+impl Serialize for TextEncoding {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for TextEncoding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let label = String::deserialize(deserializer)?;
+        label.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// UTF-16 text without byte order mark is recognized by the zero bytes of the ASCII characters
+fn detect_utf16(sample: &[u8]) -> Option<TextEncoding> {
+    let pairs = sample[..sample.len().min(UTF16_SAMPLE_SIZE)].chunks_exact(2);
+    let pair_count = pairs.len();
+    if pair_count == 0 {
+        return None;
+    }
+
+    let (even_zeros, odd_zeros) = pairs.fold((0, 0), |(even, odd), pair| {
+        (
+            even + (pair[0] == 0) as usize,
+            odd + (pair[1] == 0) as usize,
+        )
+    });
+
+    let mostly = |count: usize| count * 10 > pair_count * 4;
+    let rarely = |count: usize| count * 20 < pair_count;
+    if mostly(odd_zeros) && rarely(even_zeros) {
+        Some(TextEncoding(encoding_rs::UTF_16LE))
+    } else if mostly(even_zeros) && rarely(odd_zeros) {
+        Some(TextEncoding(encoding_rs::UTF_16BE))
+    } else {
+        None
+    }
+}
+
+/// Detect the encoding from the start of a file, `complete` is false when the sample is only
+/// the first part of the file
+pub fn detect(sample: &[u8], complete: bool) -> TextEncoding {
+    if let Some((encoding, _)) = Encoding::for_bom(sample) {
+        return TextEncoding(encoding);
+    }
+
+    if let Some(encoding) = detect_utf16(sample) {
+        return encoding;
+    }
+
+    match std::str::from_utf8(sample) {
+        Ok(_) => return TextEncoding::UTF_8,
+        // The sample can end in the middle of a multi-byte character
+        Err(err) if err.error_len().is_none() && !complete => return TextEncoding::UTF_8,
+        Err(_) => {}
+    }
+
+    let mut detector = EncodingDetector::new();
+    detector.feed(sample, complete);
+    TextEncoding(detector.guess(None, true))
+}
+
+/// Detect the encoding from all the text of the reader, for when the start of a file is not
+/// conclusive, e.g. ASCII text that is followed by text in another encoding
+pub fn detect_reader(mut reader: impl Read) -> io::Result<TextEncoding> {
+    let mut detector = EncodingDetector::new();
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        let len = reader.read(&mut buffer)?;
+        detector.feed(&buffer[..len], len == 0);
+        if len == 0 {
+            return Ok(TextEncoding(detector.guess(None, true)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(text: &str, little_endian: bool) -> Vec<u8> {
+        text.encode_utf16()
+            .flat_map(|unit| {
+                if little_endian {
+                    unit.to_le_bytes()
+                } else {
+                    unit.to_be_bytes()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_bom() {
+        assert_eq!(detect(b"\xef\xbb\xbfname\n", true), TextEncoding::UTF_8);
+        assert_eq!(detect(b"\xff\xfen\x00", true).name(), "UTF-16LE");
+        assert_eq!(detect(b"\xfe\xff\x00n", true).name(), "UTF-16BE");
+    }
+
+    #[test]
+    fn test_detect_without_bom() {
+        assert_eq!(detect("naïve,café\n".as_bytes(), true), TextEncoding::UTF_8);
+        assert_eq!(detect(b"name,city\n", true), TextEncoding::UTF_8);
+
+        let (windows_1252, _, _) = encoding_rs::WINDOWS_1252
+            .encode("Straße,Liège,Café,Zürich,Crème brûlée,Façade,naïve,déjà vu\n");
+        assert_eq!(detect(&windows_1252, true).name(), "windows-1252");
+
+        let text = "name,city\nAlice,Gent\n";
+        assert_eq!(detect(&utf16(text, true), true).name(), "UTF-16LE");
+        assert_eq!(detect(&utf16(text, false), true).name(), "UTF-16BE");
+    }
+
+    #[test]
+    fn test_detect_reader() {
+        let mut text = "name,city\n".repeat(10_000).into_bytes();
+        let (windows_1252, _, _) =
+            encoding_rs::WINDOWS_1252.encode("Straße,Liège\nCafé,Zürich\nFaçade,Crème brûlée\n");
+        text.extend_from_slice(&windows_1252);
+
+        assert_eq!(detect(&text[..CHUNK_SIZE], false), TextEncoding::UTF_8);
+        assert_eq!(
+            detect_reader(text.as_slice()).unwrap().name(),
+            "windows-1252"
+        );
+        assert_eq!(
+            detect_reader("naïve,café\n".as_bytes()).unwrap(),
+            TextEncoding::UTF_8
+        );
+    }
+
+    #[test]
+    fn test_decode() {
+        let encoding = TextEncoding::for_label("latin1").unwrap();
+        assert_eq!(encoding.name(), "windows-1252");
+        assert_eq!(encoding.decode(b"Li\xe8ge"), "Liège");
+
+        let mut bytes = b"\xff\xfe".to_vec();
+        bytes.extend(utf16("Liège", true));
+        assert_eq!(
+            TextEncoding::for_label("utf-16le").unwrap().decode(&bytes),
+            "Liège"
+        );
+    }
+
+    #[test]
+    fn test_parse_label() {
+        assert_eq!("UTF-8".parse::<TextEncoding>(), Ok(TextEncoding::UTF_8));
+        assert_eq!(
+            "cp1252".parse::<TextEncoding>().map(TextEncoding::name),
+            Ok("windows-1252")
+        );
+        assert!("klingon".parse::<TextEncoding>().is_err());
+    }
+}
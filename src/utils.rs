@@ -30,3 +30,48 @@ pub fn error_notification(context: &str, err: Error, cx: &mut AsyncApp) {
         }
     });
 }
+
+pub fn success_notification(message: impl Into<SharedString>, cx: &mut AsyncApp) {
+    let message = message.into();
+
+    let _ = cx.update(|app| {
+        if let Some(window_handle) = app.active_window() {
+            let _ = app.update_window(window_handle, |_, window, app| {
+                window.push_notification(Notification::success(message), app);
+            });
+        }
+    });
+}
+
+/// Format a size in bytes using binary units, e.g. `1.5 MiB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64;
+    let mut unit = "B";
+    for next_unit in UNITS {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next_unit;
+    }
+
+    format!("{size:.1} {unit}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
@@ -0,0 +1,219 @@
+use polars::{
+    frame::DataFrame,
+    prelude::{
+        DataType, Expr, NamedFrom, PlSmallStr, PolarsResult, Series, SeriesMethods, col, lit,
+    },
+};
+
+/// The maximum number of distinct values that are listed for a column
+pub const MAX_DISTINCT_VALUES: usize = 1000;
+
+/// Hide the rows where the column has one of the deselected values
+/// Values are compared using their text representation, values that are not listed in the
+/// checklist (when the distinct values are truncated) are kept
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueFilter {
+    pub column: String,
+    pub excluded: Vec<String>,
+    /// Hide the rows without a value
+    pub exclude_null: bool,
+}
+
+impl ValueFilter {
+    /// A filter that keeps all the values
+    pub fn new(column: &str) -> Self {
+        Self {
+            column: column.to_string(),
+            excluded: Vec::new(),
+            exclude_null: false,
+        }
+    }
+
+    /// No value is deselected, so the filter keeps all the rows
+    pub fn is_empty(&self) -> bool {
+        self.excluded.is_empty() && !self.exclude_null
+    }
+
+    pub fn contains(&self, value: Option<&str>) -> bool {
+        match value {
+            Some(value) => !self.excluded.iter().any(|excluded| excluded == value),
+            None => !self.exclude_null,
+        }
+    }
+
+    pub fn set_selected(&mut self, value: Option<&str>, selected: bool) {
+        match value {
+            Some(value) => {
+                self.excluded.retain(|existing| existing != value);
+                if !selected {
+                    self.excluded.push(value.to_string());
+                }
+            }
+            None => self.exclude_null = !selected,
+        }
+    }
+
+    pub fn expr(&self) -> Expr {
+        let value = col(self.column.as_str()).cast(DataType::String);
+        let excluded = Series::new(PlSmallStr::from_static("values"), self.excluded.as_slice());
+
+        // The comparison is null for the rows without a value
+        value
+            .is_in(lit(excluded).implode(), false)
+            .not()
+            .fill_null(lit(!self.exclude_null))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueCount {
+    /// `None` for the missing values
+    pub value: Option<String>,
+    pub count: usize,
+}
+
+/// The distinct values of a column, the most frequent values first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DistinctValues {
+    pub values: Vec<ValueCount>,
+    /// More distinct values exist than the ones that are listed
+    pub truncated: bool,
+}
+
+/// Count the distinct values of a column, at most `limit` values are returned
+/// The missing values are always listed first so they can be selected
+pub fn distinct_values(
+    data: &DataFrame,
+    column: &str,
+    limit: usize,
+) -> PolarsResult<DistinctValues> {
+    series_distinct_values(data.column(column)?.as_materialized_series(), limit)
+}
+
+/// Count the distinct values of a series, see [`distinct_values`]
+pub fn series_distinct_values(series: &Series, limit: usize) -> PolarsResult<DistinctValues> {
+    let series = series
+        .cast(&DataType::String)?
+        .with_name(PlSmallStr::from_static("value"));
+
+    let mut values = Vec::new();
+    if series.null_count() > 0 {
+        values.push(ValueCount {
+            value: None,
+            count: series.null_count(),
+        });
+    }
+
+    let counts =
+        series
+            .drop_nulls()
+            .value_counts(true, true, PlSmallStr::from_static("count"), false)?;
+    let count_column = counts.column("count")?.cast(&DataType::UInt64)?;
+    let value_column = counts.column("value")?;
+
+    values.extend(
+        value_column
+            .str()?
+            .into_iter()
+            .zip(count_column.u64()?)
+            .take(limit)
+            .map(|(value, count)| ValueCount {
+                value: value.map(str::to_string),
+                count: count.unwrap_or_default() as usize,
+            }),
+    );
+
+    Ok(DistinctValues {
+        values,
+        truncated: counts.height() > limit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::people;
+    use polars::prelude::*;
+
+    #[test]
+    fn test_distinct_values() {
+        let distinct = distinct_values(&people(), "city", MAX_DISTINCT_VALUES).unwrap();
+        assert!(!distinct.truncated);
+        assert_eq!(distinct.values.len(), 4);
+        assert_eq!(distinct.values[0].value, None);
+        assert_eq!(distinct.values[0].count, 1);
+        assert_eq!(distinct.values[1].value.as_deref(), Some("Gent"));
+        assert_eq!(distinct.values[1].count, 2);
+
+        let distinct = distinct_values(&people(), "age", 2).unwrap();
+        assert!(distinct.truncated);
+        assert_eq!(distinct.values.len(), 2);
+        assert_eq!(distinct.values[0].value.as_deref(), Some("31"));
+    }
+
+    #[test]
+    fn test_select_values() {
+        let mut filter = ValueFilter::new("city");
+        assert!(filter.is_empty());
+        assert!(filter.contains(None));
+        assert!(filter.contains(Some("Brussel")));
+
+        filter.set_selected(Some("Brussel"), false);
+        filter.set_selected(None, false);
+        assert!(!filter.contains(Some("Brussel")));
+        assert!(!filter.contains(None));
+
+        let filtered = people().lazy().filter(filter.expr()).collect().unwrap();
+        assert_eq!(filtered.height(), 3);
+
+        filter.set_selected(Some("Brussel"), true);
+        filter.set_selected(None, true);
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    fn test_unlisted_values_are_kept() {
+        let distinct = distinct_values(&people(), "age", 2).unwrap();
+        assert!(distinct.truncated);
+
+        let mut filter = ValueFilter::new("age");
+        filter.set_selected(distinct.values[0].value.as_deref(), false);
+
+        // Only 31 is hidden, 25 and 8 are not listed but stay visible
+        let filtered = people().lazy().filter(filter.expr()).collect().unwrap();
+        let ages: Vec<_> = filtered
+            .column("age")
+            .unwrap()
+            .i32()
+            .unwrap()
+            .iter()
+            .collect();
+        assert_eq!(ages, [Some(42), Some(25), Some(8)]);
+    }
+
+    #[test]
+    fn test_value_filter() {
+        let filter = ValueFilter {
+            column: "city".to_string(),
+            excluded: vec!["Brussel".to_string()],
+            exclude_null: true,
+        };
+        let filtered = people().lazy().filter(filter.expr()).collect().unwrap();
+        assert_eq!(filtered.height(), 3);
+
+        let filter = ValueFilter {
+            exclude_null: false,
+            ..filter
+        };
+        let filtered = people().lazy().filter(filter.expr()).collect().unwrap();
+        assert_eq!(filtered.height(), 4);
+
+        let filter = ValueFilter {
+            column: "age".to_string(),
+            excluded: vec!["31".to_string()],
+            exclude_null: false,
+        };
+        let filtered = people().lazy().filter(filter.expr()).collect().unwrap();
+        assert_eq!(filtered.height(), 3);
+    }
+}
@@ -0,0 +1,479 @@
+use polars::{
+    frame::DataFrame,
+    prelude::{DataType, IntoLazy, PlSmallStr, PolarsResult, SchemaRef, SortMultipleOptions},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::filter::{self, ColumnFilter, FilterOptions};
+use crate::search;
+use crate::valuefilter::ValueFilter;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SortKey {
+    pub column: String,
+    pub descending: bool,
+}
+
+impl SortKey {
+    pub fn new(column: impl Into<String>, descending: bool) -> Self {
+        Self {
+            column: column.into(),
+            descending,
+        }
+    }
+}
+
+/// Declarative description of how a layer is presented: the active filters, search text and sort keys
+/// The visible data is always derived from the original data using this state
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ViewState {
+    /// The schema of the original data, filters are interpreted using the column types
+    schema: SchemaRef,
+    pub filters: Vec<ColumnFilter>,
+    /// The values that are selected in the value checklists of the columns
+    pub value_filters: Vec<ValueFilter>,
+    pub sort: Vec<SortKey>,
+    /// Only rows where any column contains this text are shown
+    pub search: String,
+}
+
+impl ViewState {
+    pub fn new(schema: SchemaRef) -> Self {
+        Self {
+            schema,
+            ..Default::default()
+        }
+    }
+
+    /// The state for data with another schema, the filters and sort keys of the columns
+    /// that are no longer present are dropped
+    pub fn with_schema(&self, schema: SchemaRef) -> Self {
+        let has_column = |column: &str| schema.get(column).is_some();
+        Self {
+            filters: self
+                .filters
+                .iter()
+                .filter(|filter| has_column(&filter.column))
+                .cloned()
+                .collect(),
+            value_filters: self
+                .value_filters
+                .iter()
+                .filter(|filter| has_column(&filter.column))
+                .cloned()
+                .collect(),
+            sort: self
+                .sort
+                .iter()
+                .filter(|key| has_column(&key.column))
+                .cloned()
+                .collect(),
+            search: self.search.clone(),
+            schema,
+        }
+    }
+
+    /// True if the view shows the original data
+    pub fn is_empty(&self) -> bool {
+        !self.filters.iter().any(ColumnFilter::is_active)
+            && self.value_filters.is_empty()
+            && self.search.is_empty()
+            && self.sort.is_empty()
+    }
+
+    pub fn filter(&self, column: &str) -> Option<&ColumnFilter> {
+        self.filters.iter().find(|filter| filter.column == column)
+    }
+
+    fn filter_mut(&mut self, column: &str) -> &mut ColumnFilter {
+        match self
+            .filters
+            .iter()
+            .position(|filter| filter.column == column)
+        {
+            Some(index) => &mut self.filters[index],
+            None => {
+                let dtype = self.schema.get(column).cloned().unwrap_or(DataType::String);
+                self.filters.push(ColumnFilter::new(column, dtype, ""));
+                self.filters.last_mut().expect("filter was just added")
+            }
+        }
+    }
+
+    /// Filters without text and with the default options have no effect
+    fn remove_unused_filters(&mut self) {
+        self.filters.retain(|filter| {
+            !filter.text().is_empty() || filter.options() != FilterOptions::default()
+        });
+    }
+
+    /// Set the filter text of a column, an empty text disables the filter
+    pub fn set_filter(&mut self, column: &str, text: &str) {
+        self.filter_mut(column).set_text(text);
+        self.remove_unused_filters();
+    }
+
+    pub fn set_filter_options(&mut self, column: &str, options: FilterOptions) {
+        self.filter_mut(column).set_options(options);
+        self.remove_unused_filters();
+    }
+
+    pub fn value_filter(&self, column: &str) -> Option<&ValueFilter> {
+        self.value_filters
+            .iter()
+            .find(|filter| filter.column == column)
+    }
+
+    /// Replace the value filter of a column, `None` selects all the values
+    pub fn set_value_filter(&mut self, column: &str, filter: Option<ValueFilter>) {
+        self.value_filters.retain(|filter| filter.column != column);
+        self.value_filters.extend(filter);
+    }
+
+    /// Sort on a single column, or add the column as an extra sort key when `append` is set
+    /// A `None` direction removes the column from the sort keys
+    pub fn set_sort(&mut self, column: &str, descending: Option<bool>, append: bool) {
+        if !append {
+            self.sort.clear();
+        }
+
+        let index = self.sort.iter().position(|key| key.column == column);
+        match (index, descending) {
+            (Some(index), Some(descending)) => self.sort[index].descending = descending,
+            (Some(index), None) => {
+                self.sort.remove(index);
+            }
+            (None, Some(descending)) => self.sort.push(SortKey::new(column, descending)),
+            (None, None) => {}
+        }
+    }
+
+    /// The 1-based priority and direction of the column in the sort keys
+    pub fn sort_priority(&self, column: &str) -> Option<(usize, bool)> {
+        self.sort
+            .iter()
+            .position(|key| key.column == column)
+            .map(|index| (index + 1, self.sort[index].descending))
+    }
+
+    /// The direction after clicking the column header: ascending, descending and then unsorted
+    /// Without `append` the column only cycles when it is the only sort key, otherwise the
+    /// column becomes the only sort key in ascending order
+    pub fn next_sort_direction(&self, column: &str, append: bool) -> Option<bool> {
+        if !append && self.sort.len() > 1 {
+            return Some(false);
+        }
+
+        match self.sort_priority(column) {
+            None => Some(false),
+            Some((_, false)) => Some(true),
+            Some((_, true)) => None,
+        }
+    }
+
+    /// A short description of the active filters, search text and sort keys
+    pub fn summary(&self) -> String {
+        let mut filtered_columns: Vec<&str> = Vec::new();
+        let active_columns = self
+            .filters
+            .iter()
+            .filter(|filter| filter.is_active())
+            .map(|filter| filter.column.as_str())
+            .chain(
+                self.value_filters
+                    .iter()
+                    .map(|filter| filter.column.as_str()),
+            );
+        for column in active_columns {
+            if !filtered_columns.contains(&column) {
+                filtered_columns.push(column);
+            }
+        }
+
+        let mut parts = Vec::new();
+        if !filtered_columns.is_empty() {
+            parts.push(format!("Filtered on {}", filtered_columns.join(", ")));
+        }
+
+        if !self.search.is_empty() {
+            parts.push(format!("Search \"{}\"", self.search));
+        }
+
+        if !self.sort.is_empty() {
+            let keys: Vec<String> = self
+                .sort
+                .iter()
+                .map(|key| format!("{} {}", key.column, if key.descending { "↓" } else { "↑" }))
+                .collect();
+            parts.push(format!("Sorted on {}", keys.join(", ")));
+        }
+
+        parts.join(" · ")
+    }
+
+    /// Calculate the view of the original data
+    pub fn apply(&self, data: DataFrame) -> PolarsResult<DataFrame> {
+        let mut data = filter::apply_filters(data, &self.filters)?;
+        if !self.value_filters.is_empty() {
+            let mut lazy_df = data.lazy();
+            for filter in &self.value_filters {
+                lazy_df = lazy_df.filter(filter.expr());
+            }
+            data = lazy_df.collect()?;
+        }
+
+        if let Some(search) = search::search_expr(data.schema(), &self.search) {
+            data = data.lazy().filter(search).collect()?;
+        }
+
+        if self.sort.is_empty() {
+            return Ok(data);
+        }
+
+        let columns: Vec<PlSmallStr> = self
+            .sort
+            .iter()
+            .map(|key| PlSmallStr::from(key.column.as_str()))
+            .collect();
+        let sort_options = SortMultipleOptions::default()
+            .with_order_descending_multi(self.sort.iter().map(|key| key.descending))
+            .with_multithreaded(true)
+            .with_maintain_order(true)
+            .with_nulls_last(true);
+
+        data.lazy().sort(columns, sort_options).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::people;
+    use polars::prelude::*;
+
+    fn names(df: &DataFrame) -> Vec<String> {
+        df.column("name")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .map(|name| name.unwrap_or_default().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_multi_column_sort() {
+        let data = df!(
+            "region" => ["North", "South", "North", "South", "North"],
+            "date" => [3, 1, 5, 4, 3],
+            "name" => ["a", "b", "c", "d", "e"],
+        )
+        .unwrap();
+
+        let mut state = ViewState::default();
+        state.set_sort("region", Some(false), false);
+        state.set_sort("date", Some(true), true);
+        assert_eq!(state.sort_priority("region"), Some((1, false)));
+        assert_eq!(state.sort_priority("date"), Some((2, true)));
+        assert_eq!(state.sort_priority("name"), None);
+
+        assert_eq!(
+            names(&state.apply(data).unwrap()),
+            ["c", "a", "e", "d", "b"]
+        );
+    }
+
+    #[test]
+    fn test_set_sort() {
+        let mut state = ViewState::default();
+        state.set_sort("a", Some(false), false);
+        state.set_sort("b", Some(false), true);
+        state.set_sort("a", Some(true), true);
+        assert_eq!(
+            state.sort,
+            [SortKey::new("a", true), SortKey::new("b", false)]
+        );
+
+        state.set_sort("a", None, true);
+        assert_eq!(state.sort, [SortKey::new("b", false)]);
+
+        // Without append the column becomes the only sort key
+        state.set_sort("c", Some(true), false);
+        assert_eq!(state.sort, [SortKey::new("c", true)]);
+
+        state.set_sort("c", None, false);
+        assert!(state.sort.is_empty());
+    }
+
+    #[test]
+    fn test_next_sort_direction() {
+        let mut state = ViewState::default();
+        state.set_sort("a", Some(false), false);
+        state.set_sort("b", Some(true), true);
+
+        assert_eq!(state.next_sort_direction("a", true), Some(true));
+        assert_eq!(state.next_sort_direction("b", true), None);
+        assert_eq!(state.next_sort_direction("c", true), Some(false));
+    }
+
+    #[test]
+    fn test_next_sort_direction_without_append() {
+        let mut state = ViewState::default();
+        state.set_sort("a", Some(false), false);
+        state.set_sort("b", Some(true), true);
+
+        // A plain click on a secondary key sorts on that column only, starting ascending
+        assert_eq!(state.next_sort_direction("b", false), Some(false));
+        assert_eq!(state.next_sort_direction("a", false), Some(false));
+
+        state.set_sort("b", Some(false), false);
+        assert_eq!(state.next_sort_direction("b", false), Some(true));
+        state.set_sort("b", Some(true), false);
+        assert_eq!(state.next_sort_direction("b", false), None);
+        assert_eq!(state.next_sort_direction("a", false), Some(false));
+    }
+
+    #[test]
+    fn test_with_schema_drops_missing_columns() {
+        let mut state = ViewState::new(people().schema().clone());
+        state.set_filter("name", "a");
+        state.set_filter("age", "> 30");
+        state.set_value_filter(
+            "age",
+            Some(ValueFilter {
+                column: "age".to_string(),
+                excluded: vec!["42".to_string()],
+                exclude_null: false,
+            }),
+        );
+        state.set_sort("age", Some(true), false);
+        state.search = "li".to_string();
+
+        let data = people().drop("name").unwrap();
+        let state = state.with_schema(data.schema().clone());
+        assert!(state.filter("name").is_none());
+        assert!(state.filter("age").is_some());
+        assert!(state.value_filter("age").is_some());
+        assert_eq!(state.sort, [SortKey::new("age", true)]);
+        assert_eq!(state.search, "li");
+    }
+
+    #[test]
+    fn test_empty_state_keeps_original_order() {
+        let state = ViewState::default();
+        assert!(state.is_empty());
+        assert_eq!(
+            names(&state.apply(people()).unwrap()),
+            ["Alice", "Bob", "alicia", "Charlie", "O'Brien, Dave"]
+        );
+    }
+
+    #[test]
+    fn test_filtered_data_stays_sorted() {
+        let mut state = ViewState {
+            sort: vec![SortKey::new("age", false)],
+            ..Default::default()
+        };
+        assert_eq!(
+            names(&state.apply(people()).unwrap()),
+            ["O'Brien, Dave", "alicia", "Alice", "Charlie", "Bob"]
+        );
+
+        state.set_filter("name", "ali");
+        assert_eq!(names(&state.apply(people()).unwrap()), ["alicia", "Alice"]);
+
+        // Removing the sort restores the file order
+        state.sort.clear();
+        assert_eq!(names(&state.apply(people()).unwrap()), ["Alice", "alicia"]);
+    }
+
+    #[test]
+    fn test_search_is_combined_with_filters() {
+        let mut state = ViewState {
+            search: "LI".to_string(),
+            ..Default::default()
+        };
+        assert!(!state.is_empty());
+        assert_eq!(
+            names(&state.apply(people()).unwrap()),
+            ["Alice", "alicia", "Charlie"]
+        );
+
+        state.set_filter("age", "< 30");
+        assert_eq!(names(&state.apply(people()).unwrap()), ["alicia"]);
+    }
+
+    #[test]
+    fn test_value_filter() {
+        let mut state = ViewState::default();
+        state.set_value_filter(
+            "name",
+            Some(ValueFilter {
+                column: "name".to_string(),
+                excluded: vec!["Charlie".to_string(), "alicia".to_string()],
+                exclude_null: false,
+            }),
+        );
+        assert!(!state.is_empty());
+        assert_eq!(
+            names(&state.apply(people()).unwrap()),
+            ["Alice", "Bob", "O'Brien, Dave"]
+        );
+
+        state.set_filter("age", "< 40");
+        assert_eq!(
+            names(&state.apply(people()).unwrap()),
+            ["Alice", "O'Brien, Dave"]
+        );
+
+        state.set_value_filter("name", None);
+        assert!(state.value_filter("name").is_none());
+        assert_eq!(
+            names(&state.apply(people()).unwrap()),
+            ["Alice", "alicia", "Charlie", "O'Brien, Dave"]
+        );
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut state = ViewState::default();
+        assert_eq!(state.summary(), "");
+
+        state.set_filter("name", "a");
+        state.set_filter("age", "> abc");
+        state.set_sort("age", Some(true), false);
+        state.set_sort("name", Some(false), true);
+        state.search = "x".to_string();
+        assert_eq!(
+            state.summary(),
+            "Filtered on name · Search \"x\" · Sorted on age ↓, name ↑"
+        );
+    }
+
+    #[test]
+    fn test_set_filter() {
+        let mut state = ViewState::default();
+        state.set_filter("name", "a");
+        state.set_filter("name", "al");
+        assert_eq!(state.filter("name").map(|f| f.text()), Some("al"));
+
+        state.set_filter("name", "");
+        assert!(state.filter("name").is_none());
+        assert!(state.is_empty());
+    }
+
+    #[test]
+    fn test_filter_options_are_kept_without_text() {
+        let mut state = ViewState::default();
+        let options = FilterOptions {
+            regex: true,
+            ..Default::default()
+        };
+
+        state.set_filter_options("name", options);
+        state.set_filter("name", "^A");
+        state.set_filter("name", "");
+        assert_eq!(state.filter("name").map(|f| f.options()), Some(options));
+        assert!(state.is_empty());
+    }
+}